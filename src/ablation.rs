@@ -0,0 +1,47 @@
+use crate::benchmark::LaunchOptions;
+use crate::runs::BenchmarkRun;
+use crate::stats::{mean, mean_difference};
+
+
+/// Baseline with only the required mods, each selected mod on its own, then all of them together
+pub fn ablation_steps(options: &LaunchOptions, selected: &[String]) -> Vec<(String, LaunchOptions)> {
+    let with_mods = |mods: Vec<String>| LaunchOptions { mods: Some(mods), ..options.clone() };
+    let mut steps = vec![("Baseline".to_string(), with_mods(Vec::new()))];
+    for name in selected {
+        steps.push((format!("+{}", name), with_mods(vec![name.clone()])));
+    }
+    if selected.len() > 1 {
+        steps.push(("All mods".to_string(), with_mods(selected.to_vec())));
+    }
+    steps
+}
+
+/// One line per step with its CPS gain over the baseline and the 95% confidence interval of
+/// that gain. Like `compare_report`, the statistics use each run's average rather than the
+/// samples, which depend on each other.
+pub fn ablation_report(results: &[Vec<BenchmarkRun>]) -> Vec<String> {
+    let steps: Vec<(String, Vec<f32>)> = results
+        .iter()
+        .filter_map(|runs| {
+            let label = runs.first()?.label.clone();
+            Some((label, runs.iter().map(|run| run.average_cps).collect()))
+        })
+        .collect();
+    let Some(((_, baseline), rest)) = steps.split_first() else {
+        return Vec::new();
+    };
+
+    let mut lines = vec![format!("Baseline: {:.1} CPS", mean(baseline))];
+    for (label, averages) in rest {
+        let (gain, margin) = mean_difference(baseline, averages);
+        if baseline.len() < 2 || averages.len() < 2 {
+            lines.push(format!("{}: {:.1} CPS, {:+.1} CPS", label, mean(averages), gain));
+            continue;
+        }
+        lines.push(format!(
+            "{}: {:.1} CPS, {:+.1} CPS (95% CI {:+.1} to {:+.1})",
+            label, mean(averages), gain, gain - margin, gain + margin
+        ));
+    }
+    lines
+}
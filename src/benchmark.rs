@@ -1,36 +1,243 @@
-use crate::io::{java_dir, server_dir};
+use crate::ablation::{ablation_report, ablation_steps};
+use crate::scaling::{scaling_report, scaling_steps};
+use crate::compare::set_run_groups;
+use crate::gc_log::{gc_log_file, gc_log_flag};
+use crate::io::{set_c2me_threads, set_server_property, ServerInstance};
+use crate::java::{arg_flags, gc_flag, gc_thread_flags, java_bin, jvm_heap, JvmHeap};
+use crate::jfr::{jfr_flag, keep_recording, recording_file, JfrSummary};
+use crate::memory::COMPRESSED_OOPS_LIMIT_MB;
+use crate::mods::{incompatible_mods, mod_warnings, restore_mods, sync_mods, InstalledMod};
+use crate::preflight::preflight_checks;
+use crate::runs::{iteration_report, BenchmarkRun};
+use crate::server_log::{keep_log, push_line, reset_log};
+use crate::slint_utils::{string_vec_to_rc, toggled_names};
 use crate::{App, BenchmarkingStatus, Info};
+use log::{debug, error, info, warn};
 use std::cell::RefCell;
-use std::io::{BufRead, BufReader};
-use std::path::PathBuf;
-use std::process::{Command, Stdio};
+use std::io::{self, BufRead, BufReader, Write};
+use std::process::{Command, ExitStatus, Stdio};
 use std::rc::Rc;
 use std::str::FromStr;
 use std::sync::mpsc;
 use std::sync::mpsc::Sender;
+use std::time::{Duration, Instant};
 use std::{fs, thread};
-use slint::{ComponentHandle, Weak};
+use slint::{ComponentHandle, SharedString, Weak};
 
 pub enum RunningMsg {
     Progress(f32),
     Result(f32),
+    /// A console line and whether it came from stderr
+    Log(String, bool),
+    /// Mods active for the run, sent before the server starts
+    Mods(Vec<InstalledMod>),
+    /// Missing dependencies, conflicts and unreadable jars found before launch
+    Warnings(Vec<String>),
+    /// Mods built for other Minecraft or loader versions. The server isn't started.
+    Incompatible(Vec<String>),
+    /// Problems with the machine itself, such as battery power or too little free RAM
+    Preflight(Vec<String>),
+    /// The heap the JVM settled on for the requested size
+    Heap(JvmHeap),
+    /// Summary of the flight recording, sent once the server has stopped
+    Profile(JfrSummary),
 }
 
+/// Everything besides the instance that shapes a server launch
+#[derive(Clone, Default)]
+pub struct LaunchOptions {
+    pub jvm: String,
+    /// Heap size in MB
+    pub memory_mb: u32,
+    pub args: Vec<String>,
+    /// `level-seed` for the generated world; empty lets the server pick one
+    pub seed: String,
+    /// Chunky radius in blocks. Only sent on console driven platforms, the companion mod picks its own.
+    pub radius: i32,
+    /// Logical CPUs the server may use, counted from CPU 0. The JVM is pinned to them on Linux
+    /// and told about them through `-XX:ActiveProcessorCount` everywhere. `None` uses all.
+    pub cores: Option<u32>,
+    /// C2ME's worker thread count; `None` leaves its own default
+    pub c2me_threads: Option<u32>,
+    /// Catalog names of the selected mods. The mods folder is synced to these plus the
+    /// required mods before launching; `None` leaves it untouched.
+    pub mods: Option<Vec<String>>,
+    /// Records the run with Java Flight Recorder
+    pub profile: bool,
+}
+
+impl LaunchOptions {
+    /// One launch per garbage collector checked in the Configure panel, labelled with its name,
+    /// the same steps `Preset::launch_options` gives for a preset saved from the panel
+    pub fn configurations_from_info(info: &Info) -> Vec<(String, Self)> {
+        let gcs = toggled_names(&info.get_gcs(), &info.get_gcs_toggle());
+        if gcs.is_empty() {
+            return vec![("Default GC".to_string(), Self::from_info(info, None))];
+        }
+        gcs.iter().map(|gc| (gc.clone(), Self::from_info(info, Some(gc)))).collect()
+    }
+
+    /// Options from the Configure panel with the garbage collector `gc`
+    pub fn from_info(info: &Info, gc: Option<&str>) -> Self {
+        let args = toggled_names(&info.get_args(), &info.get_args_toggle());
+        let mods = if info.get_mods_supported() {
+            toggled_names(&info.get_recommended_mod_list(), &info.get_recommended_mods_toggle())
+        } else {
+            Vec::new()
+        };
+        let positive = |value: i32| (value > 0).then_some(value as u32);
+        let mut options = Self {
+            mods: Some(mods),
+            cores: positive(info.get_cores()),
+            c2me_threads: positive(info.get_c2me_threads()),
+            profile: info.get_profile_jfr(),
+            ..Self::new(
+                &info.get_selected_jvm(),
+                info.get_ram_alloc() as u32,
+                gc,
+                &args,
+                &info.get_seed(),
+                info.get_radius(),
+            )
+        };
+        if let Some(threads) = positive(info.get_gc_threads()) {
+            options.args.extend(gc_thread_flags(threads));
+        }
+        options
+    }
+
+    pub fn new(jvm: &str, memory_mb: u32, gc: Option<&str>, args: &[String], seed: &str, radius: i32) -> Self {
+        let mut flags: Vec<String> = gc.and_then(gc_flag).into_iter().collect();
+        flags.extend(arg_flags(args));
+        Self {
+            jvm: jvm.to_string(),
+            memory_mb,
+            args: flags,
+            seed: seed.to_string(),
+            radius,
+            cores: None,
+            c2me_threads: None,
+            mods: None,
+            profile: false,
+        }
+    }
+}
+
+/// How often each configuration is run
+#[derive(Clone, Copy)]
+pub struct Iterations {
+    pub count: u32,
+    /// Pause between runs in seconds, letting the CPU cool down
+    pub cooldown: u32,
+}
+
+impl Iterations {
+    pub fn from_info(info: &Info) -> Self {
+        Self {
+            count: info.get_iterations().max(1) as u32,
+            cooldown: info.get_cooldown().max(0) as u32,
+        }
+    }
+}
+
+/// What a batch compares
+#[derive(Clone, Copy)]
+pub enum Sweep {
+    /// Every configuration as is
+    Configurations,
+    /// The first configuration without mods, with each of its mods and with all of them
+    Ablation,
+    /// The first configuration on 1, 2, 4, … cores up to its core limit or every core
+    Scaling,
+}
+
+/// Turns the results of every step into the lines of the Finished screen
+pub type Report = fn(&[Vec<BenchmarkRun>]) -> Vec<String>;
+
+/// Steps to run for `sweep` and the report to show afterwards
+pub fn plan(configurations: Vec<(String, LaunchOptions)>, sweep: Sweep) -> (Vec<(String, LaunchOptions)>, Report) {
+    let first = configurations.first().map(|(_, options)| options.clone());
+    match (sweep, first) {
+        (Sweep::Ablation, Some(options)) => {
+            let selected = options.mods.clone().unwrap_or_default();
+            (ablation_steps(&options, &selected), ablation_report)
+        }
+        (Sweep::Scaling, Some(options)) => {
+            let max_cores = options.cores.unwrap_or_else(logical_cpus);
+            (scaling_steps(&options, max_cores), scaling_report)
+        }
+        _ => (configurations, iteration_report),
+    }
+}
+
+pub fn logical_cpus() -> u32 {
+    thread::available_parallelism().map_or(1, |cores| cores.get() as u32)
+}
+
+enum BatchMsg {
+    /// Finished run count, total run count and label of the run about to start
+    Run(usize, usize, String),
+    /// Progress of the current run
+    Progress(f32),
+    /// A console line of the current run and whether it came from stderr
+    Log(String, bool),
+    Warnings(Vec<String>),
+    Preflight(Vec<String>),
+    /// Report lines and the batch ID
+    Done(Vec<String>, String),
+    Error(String),
+}
+
+/// Runs the Configure panel's setup, or its ablation or scaling sweep when checked
 pub fn start_benchmark(app: &Weak<App>) {
-    let (tx, rx) = mpsc::channel::<RunningMsg>();
-    let weak_app_timer = app.clone();
     let non_weak_app = app.unwrap();
+    let info = non_weak_app.global::<Info>();
+    let sweep = if info.get_ablation() && info.get_mods_supported() {
+        Sweep::Ablation
+    } else if info.get_scaling_sweep() {
+        Sweep::Scaling
+    } else {
+        Sweep::Configurations
+    };
+    let (steps, report) = plan(LaunchOptions::configurations_from_info(&info), sweep);
+    start_batch(app, ServerInstance::from_info(&info), steps, Iterations::from_info(&info), report);
+}
 
-    let mc_ver = non_weak_app.global::<Info>().get_selected_minecraft_version();
-    let jvm = non_weak_app.global::<Info>().get_selected_jvm();
-    let memory = non_weak_app.global::<Info>().get_memory_capacity_gb();
-    // TODO Parse JVM Flags
+/// Runs every step in the background, then shows `report` of the results on the Finished screen
+pub fn start_batch(app: &Weak<App>, instance: ServerInstance, steps: Vec<(String, LaunchOptions)>, iterations: Iterations, report: Report) {
+    let (tx, rx) = mpsc::channel::<BatchMsg>();
+    let weak_app_timer = app.clone();
+    app.unwrap().global::<Info>().set_mod_warnings(Default::default());
+    app.unwrap().global::<Info>().set_preflight_warnings(Default::default());
+    let log = reset_log(&app.unwrap().global::<Info>());
 
-    thread::spawn({
-        let tx = tx.clone();
-        move || {
-            launch_jar(mc_ver.to_string(), jvm.to_string(), memory as u32, vec![], Some(tx));
-        }
+    thread::spawn(move || {
+        let result = run_batch(
+            &instance,
+            &steps,
+            iterations,
+            |done, total, label| {
+                tx.send(BatchMsg::Run(done, total, label.to_string())).ok();
+            },
+            |msg| {
+                let forwarded = match msg {
+                    RunningMsg::Progress(p) => BatchMsg::Progress(*p),
+                    RunningMsg::Log(line, stderr) => BatchMsg::Log(line.clone(), *stderr),
+                    RunningMsg::Warnings(w) => BatchMsg::Warnings(w.clone()),
+                    RunningMsg::Preflight(w) => BatchMsg::Preflight(w.clone()),
+                    _ => return,
+                };
+                tx.send(forwarded).ok();
+            },
+        );
+        let msg = match result {
+            Ok(results) => {
+                let batch = results.iter().flatten().next().map(|run| run.batch.clone()).unwrap_or_default();
+                BatchMsg::Done(report(&results), batch)
+            }
+            Err(e) => BatchMsg::Error(e.to_string()),
+        };
+        tx.send(msg).ok();
     });
 
     // UI timer
@@ -38,7 +245,8 @@ pub fn start_benchmark(app: &Weak<App>) {
 
     let timer_for_cb = timer.clone();
 
-    let mut running_avg = RunningAverage::new();
+    let mut done = 0;
+    let mut total = 1;
 
     timer.borrow().start(
         slint::TimerMode::Repeated,
@@ -47,18 +255,44 @@ pub fn start_benchmark(app: &Weak<App>) {
             if let Some(app) = weak_app_timer.upgrade() {
                 while let Ok(msg) = rx.try_recv() {
                     match msg {
-                        RunningMsg::Progress(p) => {
-                            app.global::<Info>().set_progress(p);
-
-                            if p == 1.0 {
-                                let average_cps = running_avg.average();
-                                // TODO Process Average
-                                // TODO Finish
-                                app.global::<Info>().set_status(BenchmarkingStatus::Finished);
+                        BatchMsg::Run(finished, runs, label) => {
+                            (done, total) = (finished, runs);
+                            app.global::<Info>().set_progress(done as f32 / total as f32);
+                            if total > 1 {
+                                app.global::<Info>().set_run_label(SharedString::from(format!("Run {}/{}: {}", done + 1, total, label)));
                             }
+                            log.set_vec(Vec::new());
+                        }
+                        BatchMsg::Progress(p) => {
+                            app.global::<Info>().set_progress((done as f32 + p) / total as f32);
+                        }
+                        BatchMsg::Log(line, stderr) => {
+                            push_line(&log, line, stderr);
+                        }
+                        BatchMsg::Warnings(w) => {
+                            app.global::<Info>().set_mod_warnings(string_vec_to_rc(&w));
+                        }
+                        BatchMsg::Preflight(w) => {
+                            app.global::<Info>().set_preflight_warnings(string_vec_to_rc(&w));
+                        }
+                        BatchMsg::Done(lines, batch) => {
+                            app.global::<Info>().set_progress(1.0);
+                            app.global::<Info>().set_run_label(SharedString::new());
+                            app.global::<Info>().set_result_title(SharedString::from("Benchmark Finished"));
+                            app.global::<Info>().set_result_lines(string_vec_to_rc(&lines));
+                            app.global::<Info>().set_result_keys(string_vec_to_rc(&[batch]));
+                            app.global::<Info>().set_status(BenchmarkingStatus::Finished);
+                            set_run_groups(&app.global::<Info>());
+                            timer_for_cb.borrow().stop();
+                            return;
                         }
-                        RunningMsg::Result(r) => {
-                            running_avg.add(r);
+                        BatchMsg::Error(e) => {
+                            app.global::<Info>().set_progress(0.0);
+                            app.global::<Info>().set_run_label(SharedString::new());
+                            app.global::<Info>().set_instance_status(SharedString::from(format!("Benchmark failed: {}", e)));
+                            app.global::<Info>().set_status(BenchmarkingStatus::Configure);
+                            timer_for_cb.borrow().stop();
+                            return;
                         }
                     }
                 }
@@ -69,55 +303,268 @@ pub fn start_benchmark(app: &Weak<App>) {
     );
 }
 
-pub fn launch_jar(version: String, jvm: String, memory: u32, args: Vec<String>, tx: Option<Sender<RunningMsg>>) {
-    let jvm_path: PathBuf = if cfg!(target_os = "windows") {
-        java_dir()
-            .join(jvm.to_lowercase())
-            .join("bin")
-            .join("javaw.exe")
-    } else {
-        java_dir()
-            .join(jvm.to_lowercase())
-            .join("bin")
-            .join("java")
-    };
+/// Runs every step `iterations.count` times, each on a fresh world, and saves every run.
+/// `on_run` gets the number of finished runs, the total and the label of the next run,
+/// `on_msg` every message of the running servers. Returns the runs grouped by step.
+pub fn run_batch(
+    instance: &ServerInstance,
+    steps: &[(String, LaunchOptions)],
+    iterations: Iterations,
+    mut on_run: impl FnMut(usize, usize, &str),
+    mut on_msg: impl FnMut(&RunningMsg),
+) -> io::Result<Vec<Vec<BenchmarkRun>>> {
+    // taskset would refuse CPUs the machine doesn't have, and every run before would be wasted
+    let cpus = logical_cpus();
+    if let Some(cores) = steps.iter().filter_map(|(_, options)| options.cores).find(|&cores| cores > cpus) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{} cores were requested but this machine has {} logical CPUs", cores, cpus),
+        ));
+    }
+
+    let total = steps.len() * iterations.count as usize;
+    let mut batch = String::new();
+    let mut results = Vec::new();
+    for (step, (label, options)) in steps.iter().enumerate() {
+        let mut runs = Vec::new();
+        for iteration in 1..=iterations.count {
+            let done = step * iterations.count as usize + iteration as usize - 1;
+            if done > 0 && iterations.cooldown > 0 {
+                thread::sleep(Duration::from_secs(iterations.cooldown as u64));
+            }
+
+            let name = if label.is_empty() { "Benchmark" } else { label.as_str() };
+            on_run(done, total, &format!("{} (iteration {}/{})", name, iteration, iterations.count));
+            let mut run = run_blocking(instance, options, label, &mut on_msg)?;
+            if batch.is_empty() {
+                batch = run.id.clone();
+            }
+            run.batch = batch.clone();
+            run.iteration = iteration;
+            if let Err(e) = run.save() {
+                error!("Failed to save run: {}", e);
+            }
+            runs.push(run);
+        }
+        results.push(runs);
+    }
+    Ok(results)
+}
+
+/// Runs a benchmark, passing every message of the server to `on_msg`, and keeps its console
+/// output and flight recording. Fails when the server stops before the benchmark finished.
+/// The caller saves the result.
+pub fn run_blocking(
+    instance: &ServerInstance,
+    options: &LaunchOptions,
+    label: &str,
+    mut on_msg: impl FnMut(&RunningMsg),
+) -> io::Result<BenchmarkRun> {
+    let (tx, rx) = mpsc::channel::<RunningMsg>();
+    let handle = thread::spawn({
+        let instance = instance.clone();
+        let options = options.clone();
+        move || launch_jar(&instance, &options, Some(tx))
+    });
+
+    let mut running_avg = RunningAverage::new();
+    let mut samples = Vec::new();
+    let mut mods = Vec::new();
+    let mut preflight = Vec::new();
+    let mut heap = None;
+    let mut profile = None;
+    let mut log = String::new();
+    let mut finished = false;
+    for msg in rx {
+        on_msg(&msg);
+        match msg {
+            RunningMsg::Progress(p) => finished |= p == 1.0,
+            RunningMsg::Result(r) => {
+                running_avg.add(r);
+                samples.push(r);
+            }
+            RunningMsg::Mods(m) => mods = m,
+            RunningMsg::Preflight(w) => preflight = w,
+            RunningMsg::Heap(h) => heap = Some(h),
+            RunningMsg::Profile(p) => profile = Some(p),
+            RunningMsg::Log(line, _) => {
+                log.push_str(&line);
+                log.push('\n');
+            }
+            RunningMsg::Incompatible(incompatible) => {
+                handle.join().ok();
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("Incompatible mods: {}", incompatible.join(", ")),
+                ));
+            }
+            RunningMsg::Warnings(_) => {}
+        }
+    }
+    // The server has stopped, so its GC log and flight recording are complete
+    handle.join().ok();
+    if !finished {
+        return Err(io::Error::other("The server stopped before the benchmark finished"));
+    }
+
+    let mut run = BenchmarkRun::new(instance, options, running_avg.average(), samples, mods);
+    run.label = label.to_string();
+    run.preflight = preflight;
+    run.heap = heap;
+    run.profile = profile;
+    info!("Run {} finished: {:.1} CPS from {} samples", run.id, run.average_cps, run.samples.len());
+    if let Err(e) = keep_log(&run.id, &log) {
+        error!("Failed to keep server log: {}", e);
+    }
+    if options.profile
+        && let Err(e) = keep_recording(instance, &run.id)
+    {
+        warn!("Failed to keep flight recording: {}", e);
+    }
+    Ok(run)
+}
+
+pub fn launch_jar(instance: &ServerInstance, options: &LaunchOptions, tx: Option<Sender<RunningMsg>>) {
+    if !options.seed.is_empty()
+        && let Err(e) = set_server_property(instance, "level-seed", &options.seed)
+    {
+        error!("Failed to set seed: {}", e);
+    }
+
+    if let Some(selected) = &options.mods {
+        match sync_mods(instance, selected) {
+            Ok(active) => {
+                if let Some(tx) = &tx {
+                    tx.send(RunningMsg::Mods(active)).ok();
+                }
+            }
+            Err(e) => error!("Failed to sync mods: {}", e),
+        }
+
+        let warnings = mod_warnings(instance);
+        for warning in &warnings {
+            warn!("{}", warning);
+        }
+        if let Some(tx) = &tx {
+            tx.send(RunningMsg::Warnings(warnings)).ok();
+        }
 
-    let jar_name = "fabric-server.jar";
+        let incompatible = incompatible_mods(instance);
+        if !incompatible.is_empty() {
+            restore_mods(instance).ok();
+            if let Some(tx) = &tx {
+                tx.send(RunningMsg::Incompatible(incompatible)).ok();
+            }
+            return;
+        }
+    }
 
-    let mut command = Command::new(jvm_path);
+    // A world left over from a crashed run would skip generation
+    remove_worlds(instance);
 
-    command.arg(format!("-Xms{}G", memory));
-    command.arg(format!("-Xmx{}G", memory));
+    // Only benchmark runs report back, the install-time launch just fetches libraries
+    if let Some(tx) = &tx {
+        let mut preflight = preflight_checks(options.memory_mb);
+        if let Some(heap) = jvm_heap(&options.jvm, options.memory_mb, &options.args) {
+            if !heap.compressed_oops && heap.max_heap_mb >= COMPRESSED_OOPS_LIMIT_MB as u64 {
+                preflight.push(format!("The JVM runs a {} MB heap without compressed object pointers", heap.max_heap_mb));
+            }
+            tx.send(RunningMsg::Heap(heap)).ok();
+        }
+        for warning in &preflight {
+            warn!("{}", warning);
+        }
+        tx.send(RunningMsg::Preflight(preflight)).ok();
+    }
 
-    command.args(args);
-    command.arg("-jar");
-    command.arg(jar_name);
-    command.arg("nogui");
+    if let Err(e) = set_c2me_threads(instance, options.c2me_threads) {
+        error!("Failed to set C2ME threads: {}", e);
+    }
 
-    command.current_dir(server_dir().join(&version));
+    let mut command = match options.cores {
+        Some(cores) if cfg!(target_os = "linux") => {
+            let mut taskset = Command::new("taskset");
+            taskset.arg("--cpu-list").arg(format!("0-{}", cores.max(1) - 1)).arg(java_bin(&options.jvm));
+            taskset
+        }
+        _ => Command::new(java_bin(&options.jvm)),
+    };
+    if let Some(cores) = options.cores {
+        if !cfg!(target_os = "linux") {
+            warn!("The server is only pinned to cores on Linux, here it just sees {} through -XX:ActiveProcessorCount", cores);
+        }
+        command.arg(format!("-XX:ActiveProcessorCount={}", cores));
+    }
+
+    command.arg(format!("-Xms{}M", options.memory_mb));
+    command.arg(format!("-Xmx{}M", options.memory_mb));
+    fs::remove_file(gc_log_file(instance)).ok();
+    command.arg(gc_log_flag());
+    if options.profile {
+        fs::remove_file(recording_file(instance)).ok();
+        command.arg(jfr_flag());
+    }
+
+    command.args(&options.args);
+    command.args(instance.launch_args());
+
+    command.current_dir(instance.dir());
+    info!("Launching {} with {}, {} MB heap", instance.name(), options.jvm, options.memory_mb);
+    debug!("Server command in {}: {:?}", instance.dir().display(), command);
+    let started = Instant::now();
 
     if let Some (tx) = tx {
+        // Without the companion mod, Chunky has to be driven through the console
+        let console_driven = !instance.platform.has_companion();
         command
-            .stdin(Stdio::inherit())
+            .stdin(if console_driven { Stdio::piped() } else { Stdio::inherit() })
             .stdout(Stdio::piped())
-            .stderr(Stdio::inherit());
+            .stderr(Stdio::piped());
         match command.spawn() {
             Ok(mut child) => {
+                if let Some(stderr) = child.stderr.take() {
+                    let tx_clone = tx.clone();
+                    thread::spawn(move || {
+                        for line in BufReader::new(stderr).lines().map_while(Result::ok) {
+                            tx_clone.send(RunningMsg::Log(line, true)).ok();
+                        }
+                    });
+                }
                 if let Some(stdout) = child.stdout.take() {
                     let tx_clone = tx.clone();
+                    let mut stdin = child.stdin.take();
+                    let radius = options.radius;
                     thread::spawn(move || {
                         let reader = BufReader::new(stdout);
-                        for line in reader.lines().flatten() {
-                            println!("{}", line); // TODO Remove Debug
+                        for line in reader.lines().map_while(Result::ok) {
+                            tx_clone.send(RunningMsg::Log(line.clone(), false)).ok();
+                            if let Some(stdin) = stdin.as_mut() {
+                                if line.contains("Done (") {
+                                    writeln!(stdin, "chunky radius {}", radius).ok();
+                                    writeln!(stdin, "chunky start").ok();
+                                } else if line.contains("[Chunky] Task finished") {
+                                    writeln!(stdin, "stop").ok();
+                                }
+                            }
                             parse_console(line, &tx_clone)
                         }
                     });
 
-                    let _ = child.wait();
+                    log_exit(child.wait(), started);
                 }
             }
             Err(e) => {
-                eprintln!("Failed to launch server: {}", e);
+                error!("Failed to launch server: {}", e);
+            }
+        }
+
+        if options.profile {
+            debug!("Summarising {}", recording_file(instance).display());
+            match JfrSummary::read(&options.jvm, &recording_file(instance), started.elapsed().as_secs_f64()) {
+                Ok(summary) => {
+                    tx.send(RunningMsg::Profile(summary)).ok();
+                }
+                Err(e) => warn!("Failed to read flight recording: {}", e),
             }
         }
     } else {
@@ -127,38 +574,62 @@ pub fn launch_jar(version: String, jvm: String, memory: u32, args: Vec<String>,
             .stderr(Stdio::inherit());
         match command.spawn() {
             Ok(mut child) => {
-                let _ = child.wait();
+                log_exit(child.wait(), started);
             }
             Err(e) => {
-                eprintln!("Failed to launch server: {}", e);
+                error!("Failed to launch server: {}", e);
             }
         }
     }
 
-    // Remove world
-    let world_path = server_dir().join(version).join("world");
-    if fs::exists(&world_path).unwrap() {
-        fs::remove_dir_all(world_path).unwrap();
+    if options.mods.is_some()
+        && let Err(e) = restore_mods(instance)
+    {
+        error!("Failed to restore mods: {}", e);
+    }
+
+    remove_worlds(instance);
+}
+
+fn log_exit(status: io::Result<ExitStatus>, started: Instant) {
+    match status {
+        Ok(status) => info!("Server exited with {} after {:.1}s", status, started.elapsed().as_secs_f64()),
+        Err(e) => error!("Failed to wait for the server: {}", e),
+    }
+}
+
+fn remove_worlds(instance: &ServerInstance) {
+    for world_path in instance.world_dirs() {
+        if fs::exists(&world_path).unwrap() {
+            fs::remove_dir_all(world_path).unwrap();
+        }
     }
 }
 
 fn parse_console(line: String,  tx: &Sender<RunningMsg>) {
-    if line.contains("[Chunky]") {
-        if line.contains("%") {
-            let split1 = line.split('(').last().unwrap();
-            let split2 = split1.split('%').nth(0).unwrap();
-            let percent_complete = f32::from_str(split2).unwrap() / 100.0;
-            tx.send(RunningMsg::Progress(percent_complete)).unwrap();
-            if percent_complete != 1.0 {
-                let split3 = split1.split(':').nth(1).unwrap().trim_start();
-                let split4 = split3.split(' ').nth(0).unwrap();
-                let cps = f32::from_str(split4).unwrap();
-                tx.send(RunningMsg::Result(cps)).unwrap();
-            }
+    if let Some((progress, cps)) = chunky_progress(&line) {
+        tx.send(RunningMsg::Progress(progress)).ok();
+        if let Some(cps) = cps {
+            tx.send(RunningMsg::Result(cps)).ok();
         }
     }
 }
 
+/// Progress from 0 to 1 and the rate in chunks per second of a Chunky progress line:
+/// `[Chunky] Task running for minecraft:overworld. Processed: 1234 chunks (12.34%), ETA: 0:01:23, Rate: 123.4 cps, Current: 12, -3`.
+/// The finishing line has no rate. Lines in any other shape give `None`.
+fn chunky_progress(line: &str) -> Option<(f32, Option<f32>)> {
+    let (_, message) = line.split_once("[Chunky]")?;
+    let (_, percent) = message.split_once('(')?;
+    let (percent, _) = percent.split_once("%)")?;
+    let progress = f32::from_str(percent.trim()).ok()? / 100.0;
+    let cps = message
+        .split_once("Rate:")
+        .and_then(|(_, rate)| rate.split_once("cps"))
+        .and_then(|(rate, _)| f32::from_str(rate.trim()).ok());
+    Some((progress, cps))
+}
+
 #[derive(Default)]
 pub struct RunningAverage {
     count: u32,
@@ -182,4 +653,19 @@ impl RunningAverage {
     pub fn average(&self) -> f32 {
         self.avg
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chunky_lines() {
+        let running = "[12:00:00] [Server thread/INFO]: [Chunky] Task running for minecraft:overworld. Processed: 1234 chunks (12.34%), ETA: 0:01:23, Rate: 123.4 cps, Current: 12, -3";
+        assert_eq!(chunky_progress(running), Some((0.1234, Some(123.4))));
+        let finished = "[12:01:23 INFO]: [Chunky] Task finished for minecraft:overworld. Processed: 10000 chunks (100.00%), Total time: 0:01:23";
+        assert_eq!(chunky_progress(finished), Some((1.0, None)));
+        assert_eq!(chunky_progress("[Chunky] Task started for minecraft:overworld (radius 500)"), None);
+        assert_eq!(chunky_progress("[Chunky] Processed: 5 chunks (n/a%)"), None);
+        assert_eq!(chunky_progress("Done (3.2s)! For help, type \"help\""), None);
+    }
+}
@@ -0,0 +1,70 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use crate::runs::{load_runs, BenchmarkRun};
+
+const BUNDLE_VERSION: u32 = 1;
+
+/// A single run as shared between machines. The run carries its CPS samples, GC summary,
+/// mod hashes and `SystemInfo`; the hash catches edits made after export.
+#[derive(Serialize, Deserialize)]
+pub struct ResultBundle {
+    pub version: u32,
+    pub run: Value,
+    /// SHA-256 of `run` serialized with sorted keys
+    pub sha256: String,
+}
+
+impl ResultBundle {
+    pub fn new(run: &BenchmarkRun) -> io::Result<Self> {
+        let run = serde_json::to_value(run).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        Ok(ResultBundle { version: BUNDLE_VERSION, sha256: content_hash(&run)?, run })
+    }
+
+    /// The bundled run, once its hash checks out
+    pub fn verified_run(&self) -> io::Result<BenchmarkRun> {
+        if self.version > BUNDLE_VERSION {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                format!("Bundle version {} is newer than this SubChunker", self.version),
+            ));
+        }
+        if content_hash(&self.run)? != self.sha256 {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "Bundle hash mismatch, the run was modified"));
+        }
+        serde_json::from_value(self.run.clone())
+            .map(BenchmarkRun::upgrade)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+}
+
+// `Value` keeps object keys sorted, so the hash doesn't depend on field order
+fn content_hash(run: &Value) -> io::Result<String> {
+    let bytes = serde_json::to_vec(run).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    Ok(format!("{:x}", Sha256::digest(bytes)))
+}
+
+/// Writes `subchunker-run-<id>.json` into `dir`
+pub fn export_bundle(run: &BenchmarkRun, dir: &Path) -> io::Result<PathBuf> {
+    let json = serde_json::to_string_pretty(&ResultBundle::new(run)?)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    let path = dir.join(format!("subchunker-run-{}.json", run.id));
+    fs::write(&path, json)?;
+    Ok(path)
+}
+
+/// Checks a bundle and adds its run to the local runs, tagged as foreign
+pub fn import_bundle(path: &Path) -> io::Result<BenchmarkRun> {
+    let bundle: ResultBundle = serde_json::from_str(&fs::read_to_string(path)?)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    let mut run = bundle.verified_run()?;
+    if load_runs().iter().any(|existing| existing.id == run.id) {
+        return Err(io::Error::new(io::ErrorKind::AlreadyExists, format!("Run {} is already stored", run.id)));
+    }
+    run.foreign = true;
+    run.save()?;
+    Ok(run)
+}
@@ -0,0 +1,104 @@
+use std::env;
+use std::path::PathBuf;
+
+/// Commands that print a result and exit instead of opening the UI
+pub enum Command {
+    /// `compare <A> <B>`, each a run ID or `batch/label` group key
+    Compare(String, String),
+    /// `export --format <csv|json|md|html> [--since <date>] [--output <file>]`
+    Export,
+}
+
+/// Command line flags
+#[derive(Default)]
+pub struct CliArgs {
+    pub command: Option<Command>,
+    /// Accept the Minecraft EULA without showing the consent dialog
+    pub accept_eula: bool,
+    /// Overrides `SUBCHUNKER_HOME` and the platform data directory
+    pub data_dir: Option<PathBuf>,
+    /// Run the given preset (name or file) without the UI
+    pub headless: bool,
+    pub preset: Option<String>,
+    /// Measure each of the preset's mods against a baseline
+    pub ablation: bool,
+    /// Run the preset on 1, 2, 4, … cores
+    pub scaling: bool,
+    /// Export format, `md` when not given
+    pub format: Option<String>,
+    /// Only export runs from this date (`YYYY-MM-DD`) or Unix timestamp on
+    pub since: Option<String>,
+    /// Export file, a timestamped file in the data directory when not given
+    pub output: Option<PathBuf>,
+    /// Show debug records on the console, not just in the log file
+    pub verbose: bool,
+}
+
+impl CliArgs {
+    pub fn parse() -> Self {
+        Self::parse_from(env::args().skip(1))
+    }
+
+    /// Options taking a value accept both `--flag value` and `--flag=value`
+    fn parse_from(args: impl IntoIterator<Item = String>) -> Self {
+        let mut cli = CliArgs::default();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let (flag, inline) = match arg.split_once('=') {
+                Some((flag, value)) if flag.starts_with("--") => (flag, Some(value.to_string())),
+                _ => (arg.as_str(), None),
+            };
+            let mut value = || inline.clone().or_else(|| args.next());
+            match flag {
+                "--accept-eula" => cli.accept_eula = true,
+                "--data-dir" => cli.data_dir = value().map(PathBuf::from),
+                "--headless" => cli.headless = true,
+                "--preset" => cli.preset = value(),
+                "--ablation" => cli.ablation = true,
+                "--scaling" => cli.scaling = true,
+                "--verbose" | "-v" => cli.verbose = true,
+                "--format" => cli.format = value(),
+                "--since" => cli.since = value(),
+                "--output" => cli.output = value().map(PathBuf::from),
+                "export" => cli.command = Some(Command::Export),
+                "compare" => match (args.next(), args.next()) {
+                    (Some(a), Some(b)) => cli.command = Some(Command::Compare(a, b)),
+                    _ => eprintln!("compare needs two run IDs or group keys"),
+                },
+                _ => eprintln!("Unknown argument: {}", arg),
+            }
+        }
+        cli
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> CliArgs {
+        CliArgs::parse_from(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn values() {
+        let spaced = ["export", "--format", "csv", "--since", "2024-01-01", "--output", "out.csv", "--data-dir", "data"];
+        let joined = ["export", "--format=csv", "--since=2024-01-01", "--output=out.csv", "--data-dir=data"];
+        for cli in [parse(&spaced), parse(&joined)] {
+            assert!(matches!(cli.command, Some(Command::Export)));
+            assert_eq!(cli.format.as_deref(), Some("csv"));
+            assert_eq!(cli.since.as_deref(), Some("2024-01-01"));
+            assert_eq!(cli.output, Some(PathBuf::from("out.csv")));
+            assert_eq!(cli.data_dir, Some(PathBuf::from("data")));
+        }
+    }
+
+    #[test]
+    fn preset() {
+        let cli = parse(&["--headless", "--preset=name=with=equals", "-v"]);
+        assert!(cli.headless && cli.verbose);
+        assert_eq!(cli.preset.as_deref(), Some("name=with=equals"));
+        // `--flag=` sets an empty value rather than taking the next argument
+        assert_eq!(parse(&["--output=", "--ablation"]).output, Some(PathBuf::new()));
+    }
+}
@@ -0,0 +1,123 @@
+use std::io;
+use slint::SharedString;
+use crate::runs::{find_runs, run_groups, BenchmarkRun};
+use crate::slint_utils::string_vec_to_rc;
+use crate::system_info::differences;
+use crate::stats::{mann_whitney_u, mean, mean_difference, speedup, welch_t_test};
+use crate::Info;
+
+/// Compares `b` against `a`, each a run ID or group key, with one average CPS per run
+pub fn compare(a: &str, b: &str) -> io::Result<Vec<String>> {
+    let runs_a = find_runs(a);
+    let runs_b = find_runs(b);
+    for (key, runs) in [(a, &runs_a), (b, &runs_b)] {
+        if runs.is_empty() {
+            return Err(io::Error::new(io::ErrorKind::NotFound, format!("No run or group {}", key)));
+        }
+    }
+    Ok(compare_report(&runs_a, &runs_b))
+}
+
+/// The statistics work on each run's average, consecutive CPS samples of one run depend on
+/// each other and would make the intervals far too narrow
+pub fn compare_report(a: &[BenchmarkRun], b: &[BenchmarkRun]) -> Vec<String> {
+    let averages_a: Vec<f32> = a.iter().map(|run| run.average_cps).collect();
+    let averages_b: Vec<f32> = b.iter().map(|run| run.average_cps).collect();
+
+    let mut lines = vec![describe("A", a, &averages_a), describe("B", b, &averages_b)];
+
+    let (difference, margin) = mean_difference(&averages_a, &averages_b);
+    let (ratio, ratio_margin) = speedup(&averages_a, &averages_b);
+    match (welch_t_test(&averages_a, &averages_b), mann_whitney_u(&averages_a, &averages_b)) {
+        (Some(welch), Some(mann_whitney)) => {
+            lines.push(format!(
+                "Difference: {:+.1} CPS (95% CI {:+.1} to {:+.1})",
+                difference, difference - margin, difference + margin
+            ));
+            lines.push(format!(
+                "Speedup: {:+.1}% (95% CI {:+.1}% to {:+.1}%)",
+                (ratio - 1.0) * 100.0, (ratio - ratio_margin - 1.0) * 100.0, (ratio + ratio_margin - 1.0) * 100.0
+            ));
+            lines.push(format!("Welch's t-test: p = {:.4}", welch));
+            lines.push(format!("Mann-Whitney U: p = {:.4}", mann_whitney));
+            lines.push(if welch < 0.05 {
+                "The difference is significant at the 5% level".to_string()
+            } else {
+                "The difference is not significant at the 5% level".to_string()
+            });
+        }
+        _ => {
+            lines.push(format!("Difference: {:+.1} CPS", difference));
+            lines.push(format!("Speedup: {:+.1}%", (ratio - 1.0) * 100.0));
+            lines.push("Not enough runs for a confidence interval or significance test, each side needs two".to_string());
+        }
+    }
+
+    lines.extend(hardware_warnings(a, b));
+    lines
+}
+
+fn describe(side: &str, runs: &[BenchmarkRun], averages: &[f32]) -> String {
+    let first = &runs[0];
+    let label = if first.label.is_empty() { first.instance.as_str() } else { first.label.as_str() };
+    let origin = if first.foreign { ", imported" } else { "" };
+    let samples: usize = runs.iter().map(|run| run.samples.len()).sum();
+    format!(
+        "{}: {} on {}, {:.1} CPS ({} runs, {} samples{})",
+        side, label, first.jvm, mean(averages), runs.len(), samples, origin
+    )
+}
+
+/// Warnings for runs whose `SystemInfo` is missing or differs from the first run of `a`
+fn hardware_warnings(a: &[BenchmarkRun], b: &[BenchmarkRun]) -> Vec<String> {
+    let mut warnings = Vec::new();
+    let Some(reference) = a[0].system.as_ref() else {
+        warnings.push(format!("Warning: run {} has no system info", a[0].id));
+        return warnings;
+    };
+    for run in a.iter().chain(b) {
+        match &run.system {
+            None => warnings.push(format!("Warning: run {} has no system info", run.id)),
+            Some(system) => {
+                let differences = differences(reference, system);
+                if !differences.is_empty() {
+                    warnings.push(format!(
+                        "Warning: run {} ran on a different system ({} differ: {}, {} threads, {:.0} GB, {})",
+                        run.id, differences.join(", "), system.cpu.trim_end(), system.cores, system.memory, system.os
+                    ));
+                }
+            }
+        }
+    }
+    warnings
+}
+
+/// Fills the compare combo boxes as `key · label · CPS · runs`, newest first
+pub fn set_run_groups(info: &Info) {
+    let entries: Vec<String> = run_groups()
+        .into_iter()
+        .rev()
+        .map(|(key, runs)| {
+            let averages: Vec<f32> = runs.iter().map(|run| run.average_cps).collect();
+            let mut label = if runs[0].label.is_empty() { runs[0].instance.clone() } else { runs[0].label.clone() };
+            if runs[0].foreign {
+                label.push_str(" (foreign)");
+            }
+            format!("{} · {} · {:.1} CPS · {} runs", key, label, mean(&averages), runs.len())
+        })
+        .collect();
+    if let Some(first) = entries.first() {
+        if info.get_compare_a().is_empty() {
+            info.set_compare_a(SharedString::from(first));
+        }
+        if info.get_compare_b().is_empty() {
+            info.set_compare_b(SharedString::from(first));
+        }
+    }
+    info.set_run_groups(string_vec_to_rc(&entries));
+}
+
+/// The key at the start of a compare combo box entry
+pub fn entry_key(entry: &str) -> &str {
+    entry.split(" · ").next().unwrap_or_default()
+}
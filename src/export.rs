@@ -0,0 +1,254 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use crate::runs::{group_runs, load_runs, BenchmarkRun};
+use crate::stats::{mean, stddev};
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum ExportFormat {
+    Csv,
+    Json,
+    Markdown,
+    Html,
+}
+
+impl ExportFormat {
+    pub fn from_name(name: &str) -> Option<ExportFormat> {
+        match name.to_lowercase().as_str() {
+            "csv" => Some(ExportFormat::Csv),
+            "json" => Some(ExportFormat::Json),
+            "md" | "markdown" => Some(ExportFormat::Markdown),
+            "html" => Some(ExportFormat::Html),
+            _ => None,
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Json => "json",
+            ExportFormat::Markdown => "md",
+            ExportFormat::Html => "html",
+        }
+    }
+}
+
+/// Stored runs started at or after `since` (seconds since the Unix epoch)
+pub fn runs_since(since: Option<u64>) -> Vec<BenchmarkRun> {
+    load_runs()
+        .into_iter()
+        .filter(|run| since.is_none_or(|since| run.timestamp >= since))
+        .collect()
+}
+
+/// Stored runs whose ID, batch or group key is one of `keys`
+pub fn runs_matching(keys: &[String]) -> Vec<BenchmarkRun> {
+    load_runs()
+        .into_iter()
+        .filter(|run| keys.iter().any(|key| *key == run.id || *key == run.batch || *key == run.group_key()))
+        .collect()
+}
+
+/// Writes the runs into `dir` as `subchunker-report-<time>.<ext>` and returns the file
+pub fn export_to(runs: &[BenchmarkRun], format: ExportFormat, dir: &Path) -> io::Result<PathBuf> {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+    let path = dir.join(format!("subchunker-report-{}.{}", now, format.extension()));
+    write_export(runs, format, &path)?;
+    Ok(path)
+}
+
+pub fn write_export(runs: &[BenchmarkRun], format: ExportFormat, path: &Path) -> io::Result<()> {
+    if runs.is_empty() {
+        return Err(io::Error::new(io::ErrorKind::NotFound, "No runs to export"));
+    }
+    let contents = match format {
+        ExportFormat::Csv => to_csv(runs),
+        ExportFormat::Json => serde_json::to_string_pretty(runs)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?,
+        ExportFormat::Markdown => to_markdown(runs),
+        ExportFormat::Html => to_html(runs),
+    };
+    fs::write(path, contents)
+}
+
+// CSV
+const CSV_HEADER: &str = "id,batch,label,iteration,date,instance,platform,minecraft_version,loader_version,jvm,memory_mb,max_heap_mb,args,seed,radius,average_cps,samples,gc_collections,gc_pause_ms,foreign,preflight_warnings,mods";
+
+fn to_csv(runs: &[BenchmarkRun]) -> String {
+    let mut csv = String::from(CSV_HEADER);
+    csv.push('\n');
+    for run in runs {
+        let row = [
+            run.id.clone(),
+            run.batch.clone(),
+            run.label.clone(),
+            run.iteration.to_string(),
+            format_date(run.timestamp),
+            run.instance.clone(),
+            run.platform.clone(),
+            run.minecraft_version.clone(),
+            run.loader_version.clone(),
+            run.jvm.clone(),
+            run.memory_mb.to_string(),
+            run.heap.as_ref().map(|heap| heap.max_heap_mb.to_string()).unwrap_or_default(),
+            run.args.join(" "),
+            run.seed.clone(),
+            run.radius.to_string(),
+            format!("{:.2}", run.average_cps),
+            run.samples.len().to_string(),
+            run.gc.as_ref().map(|gc| gc.collections.to_string()).unwrap_or_default(),
+            run.gc.as_ref().map(|gc| format!("{:.1}", gc.total_pause_ms)).unwrap_or_default(),
+            run.foreign.to_string(),
+            run.preflight.join("; "),
+            mod_list(run),
+        ];
+        let fields: Vec<String> = row.iter().map(|field| csv_field(field)).collect();
+        csv.push_str(&fields.join(","));
+        csv.push('\n');
+    }
+    csv
+}
+
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+// Reports
+fn mod_list(run: &BenchmarkRun) -> String {
+    run.mods
+        .iter()
+        .map(|m| format!("{} {}", m.id, m.version.as_deref().unwrap_or("?")))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Per configuration summary of the exported runs: label, mean, stddev and run count
+fn summaries(runs: &[BenchmarkRun]) -> Vec<(String, f64, f64, usize)> {
+    group_runs(runs.to_vec())
+        .into_iter()
+        .filter_map(|(key, group)| {
+            let first = group.first()?;
+            let label = if first.label.is_empty() { first.instance.clone() } else { format!("{} ({})", first.label, first.instance) };
+            let averages: Vec<f32> = group.iter().map(|run| run.average_cps).collect();
+            Some((format!("{}: {}", key, label), mean(&averages), stddev(&averages), group.len()))
+        })
+        .collect()
+}
+
+const CHART_WIDTH: usize = 40;
+
+fn to_markdown(runs: &[BenchmarkRun]) -> String {
+    let mut md = String::from("# SubChunker Benchmark Report\n\n");
+    md.push_str(&format!("{} runs from {} to {}\n\n", runs.len(), format_date(runs[0].timestamp), format_date(runs[runs.len() - 1].timestamp)));
+
+    md.push_str("## Results\n\n| Configuration | Mean CPS | Std dev | Runs |\n|---|---:|---:|---:|\n");
+    let summaries = summaries(runs);
+    for (label, mean, stddev, count) in &summaries {
+        md.push_str(&format!("| {} | {:.1} | {:.1} | {} |\n", label, mean, stddev, count));
+    }
+
+    md.push_str("\n## Chart\n\n```\n");
+    let max = summaries.iter().map(|s| s.1).fold(0.0, f64::max);
+    for (label, mean, _, _) in &summaries {
+        let bar = if max > 0.0 { (mean / max * CHART_WIDTH as f64).round() as usize } else { 0 };
+        md.push_str(&format!("{:<width$} {:.1}\n{}\n", label, mean, "█".repeat(bar), width = CHART_WIDTH));
+    }
+    md.push_str("```\n\n## Runs\n\n| Run | Date | Label | Instance | JVM | Memory | Flags | Seed | Radius | Mods | CPS |\n|---|---|---|---|---|---:|---|---|---:|---|---:|\n");
+    for run in runs {
+        md.push_str(&format!(
+            "| {} | {} | {} | {} | {} | {} MB | {} | {} | {} | {} | {:.1} |\n",
+            run.id, format_date(run.timestamp), run.label, run.instance, run.jvm, run.memory_mb,
+            run.args.join(" "), run.seed, run.radius, mod_list(run), run.average_cps
+        ));
+    }
+    md
+}
+
+fn to_html(runs: &[BenchmarkRun]) -> String {
+    let mut html = String::from(concat!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>SubChunker Benchmark Report</title>\n",
+        "<style>body{font-family:sans-serif;margin:2em}table{border-collapse:collapse}",
+        "td,th{border:1px solid #ccc;padding:4px 8px}.bar{background:#4a90d9;height:14px}</style>\n",
+        "</head>\n<body>\n<h1>SubChunker Benchmark Report</h1>\n"
+    ));
+    html.push_str(&format!(
+        "<p>{} runs from {} to {}</p>\n",
+        runs.len(), format_date(runs[0].timestamp), format_date(runs[runs.len() - 1].timestamp)
+    ));
+
+    let summaries = summaries(runs);
+    let max = summaries.iter().map(|s| s.1).fold(0.0, f64::max);
+    html.push_str("<h2>Results</h2>\n<table>\n<tr><th>Configuration</th><th>Mean CPS</th><th>Std dev</th><th>Runs</th><th></th></tr>\n");
+    for (label, mean, stddev, count) in &summaries {
+        let width = if max > 0.0 { mean / max * 300.0 } else { 0.0 };
+        html.push_str(&format!(
+            "<tr><td>{}</td><td>{:.1}</td><td>{:.1}</td><td>{}</td><td><div class=\"bar\" style=\"width:{:.0}px\"></div></td></tr>\n",
+            escape(label), mean, stddev, count, width
+        ));
+    }
+
+    html.push_str("</table>\n<h2>Runs</h2>\n<table>\n<tr><th>Run</th><th>Date</th><th>Label</th><th>Instance</th><th>JVM</th><th>Memory</th><th>Flags</th><th>Seed</th><th>Radius</th><th>Mods</th><th>CPS</th></tr>\n");
+    for run in runs {
+        html.push_str(&format!(
+            "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{} MB</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{:.1}</td></tr>\n",
+            run.id, format_date(run.timestamp), escape(&run.label), escape(&run.instance), escape(&run.jvm), run.memory_mb,
+            escape(&run.args.join(" ")), escape(&run.seed), run.radius, escape(&mod_list(run)), run.average_cps
+        ));
+    }
+    html.push_str("</table>\n</body>\n</html>\n");
+    html
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+// Dates
+/// `YYYY-MM-DD HH:MM` in UTC
+pub fn format_date(timestamp: u64) -> String {
+    let days = (timestamp / 86_400) as i64;
+    let (year, month, day) = civil_from_days(days);
+    let seconds = timestamp % 86_400;
+    format!("{:04}-{:02}-{:02} {:02}:{:02}", year, month, day, seconds / 3600, seconds % 3600 / 60)
+}
+
+/// Accepts `YYYY-MM-DD` (UTC midnight) or seconds since the Unix epoch
+pub fn parse_since(since: &str) -> Option<u64> {
+    if let Ok(timestamp) = since.parse::<u64>() {
+        return Some(timestamp);
+    }
+    let mut parts = since.splitn(3, '-').map(|part| part.parse::<i64>().ok());
+    let (year, month, day) = (parts.next()??, parts.next()??, parts.next()??);
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+    u64::try_from(days_from_civil(year, month, day) * 86_400).ok()
+}
+
+// Howard Hinnant's civil calendar algorithms
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 { month_index + 3 } else { month_index - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
@@ -0,0 +1,62 @@
+use std::collections::BTreeSet;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use serde::{Deserialize, Serialize};
+use crate::io::ServerInstance;
+
+// Relative to the instance folder, the server's working directory. An absolute path would
+// break `-Xlog` parsing on Windows, where drive letters contain a colon.
+const GC_LOG: &str = "gc.log";
+
+/// JVM flag writing the GC log of a run, replacing the previous one
+pub fn gc_log_flag() -> String {
+    format!("-Xlog:gc:file={}::filecount=0", GC_LOG)
+}
+
+pub fn gc_log_file(instance: &ServerInstance) -> PathBuf {
+    instance.dir().join(GC_LOG)
+}
+
+/// Collections and stop-the-world pauses from a run's GC log
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct GcSummary {
+    pub collections: u32,
+    pub pauses: u32,
+    pub total_pause_ms: f64,
+    pub max_pause_ms: f64,
+}
+
+impl GcSummary {
+    /// Reads `-Xlog:gc` output such as
+    /// `[1.234s][info][gc] GC(3) Pause Young (Normal) (G1 Evacuation Pause) 25M->4M(258M) 2.345ms`.
+    /// ZGC only logs its pauses under `gc+phases`, so its runs report collections alone.
+    pub fn read(instance: &ServerInstance) -> io::Result<GcSummary> {
+        let log = fs::read_to_string(gc_log_file(instance))?;
+        let mut collections = BTreeSet::new();
+        let mut summary = GcSummary::default();
+        for line in log.lines() {
+            let Some((_, rest)) = line.split_once("GC(") else {
+                continue;
+            };
+            if let Some((id, _)) = rest.split_once(')') {
+                collections.insert(id.to_string());
+            }
+            if !rest.contains("Pause") {
+                continue;
+            }
+            let pause = rest
+                .split_whitespace()
+                .last()
+                .and_then(|last| last.strip_suffix("ms"))
+                .and_then(|ms| ms.parse::<f64>().ok());
+            if let Some(pause) = pause {
+                summary.pauses += 1;
+                summary.total_pause_ms += pause;
+                summary.max_pause_ms = summary.max_pause_ms.max(pause);
+            }
+        }
+        summary.collections = collections.len() as u32;
+        Ok(summary)
+    }
+}
@@ -0,0 +1,118 @@
+use std::io;
+use std::sync::mpsc;
+use std::thread;
+use crate::benchmark::{plan, run_batch, RunningMsg, Sweep};
+use crate::io::{install_server, InstallerMsg};
+use crate::mods::{get_mods, get_url, incompatible_mods, install_local_mod, restore_mods, sync_mods, ModCatalog};
+use crate::presets::Preset;
+
+/// Installs and benchmarks a preset without opening the UI. With `ablation`, measures each
+/// of the preset's mods on its own instead of running every GC. With `scaling`, runs the first
+/// GC on 1, 2, 4, … cores up to the preset's core limit or every core.
+pub fn run(preset: &str, ablation: bool, scaling: bool) -> io::Result<()> {
+    let preset = Preset::load(preset)?;
+    let instance = preset.instance()?;
+    println!("Benchmarking preset {} on {}", preset.name, instance.name());
+
+    // Install
+    let (tx, rx) = mpsc::channel::<InstallerMsg>();
+    let printer = thread::spawn(move || {
+        for msg in rx {
+            if let InstallerMsg::InstallingMsg(s) = msg {
+                println!("{}", s);
+            }
+        }
+    });
+    let installed = install_server(&instance, &preset.jvm, preset.ram_mb(), &tx);
+    drop(tx);
+    printer.join().ok();
+    installed?;
+
+    // Local catalog jars are copied in like the UI does; Modrinth mods have to be downloaded by hand
+    restore_mods(&instance)?;
+    let catalog = ModCatalog::load();
+    let selected: Vec<String> = if instance.platform.supports_mods() {
+        preset.mods.iter().map(|m| m.name.clone()).collect()
+    } else {
+        Vec::new()
+    };
+    let mut wanted: Vec<String> = catalog.required(instance.platform).iter().map(|m| m.name.clone()).collect();
+    wanted.extend(selected.iter().cloned());
+    for name in &wanted {
+        if !get_mods(&instance).contains(&catalog.mod_id(name)) {
+            install_local_mod(name, &instance)?;
+        }
+    }
+    let installed_mods = get_mods(&instance);
+    let missing: Vec<String> = wanted
+        .into_iter()
+        .filter(|m| !installed_mods.contains(&catalog.mod_id(m)))
+        .map(|m| match get_url(m.clone(), instance.mc_ver.clone(), instance.platform) {
+            Some(url) => format!("{} ({})", m, url),
+            None => m,
+        })
+        .collect();
+    if !missing.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("Download these mods into {} first: {}", instance.mods_dir().display(), missing.join(", ")),
+        ));
+    }
+
+    // Check the folder the runs will see, so an incompatible mod fails before the first run
+    sync_mods(&instance, &selected)?;
+    let incompatible = incompatible_mods(&instance);
+    restore_mods(&instance)?;
+    if !incompatible.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Incompatible mods: {}", incompatible.join(", ")),
+        ));
+    }
+
+    let mismatched = preset.pin_mismatches(&instance);
+    if !mismatched.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Mod versions differ from the preset: {}", mismatched.join(", ")),
+        ));
+    }
+
+    // Run
+    let iterations = preset.iterations();
+    let sweep = if ablation {
+        Sweep::Ablation
+    } else if scaling {
+        Sweep::Scaling
+    } else {
+        Sweep::Configurations
+    };
+    let (steps, report) = plan(preset.launch_options(), sweep);
+
+    let results = run_batch(
+        &instance,
+        &steps,
+        iterations,
+        |done, total, label| {
+            println!("Run {}/{}: {}", done + 1, total, label);
+        },
+        // The full console is kept in each run's server.log, only errors are echoed
+        |msg| {
+            if let RunningMsg::Log(line, true) = msg {
+                eprintln!("{}", line);
+            }
+        },
+    )?;
+    for run in results.iter().flatten() {
+        let mods: Vec<String> = run
+            .mods
+            .iter()
+            .map(|m| format!("{} {}", m.id, m.version.as_deref().unwrap_or("?")))
+            .collect();
+        println!("{} #{}: {:.1} CPS with {}", run.label, run.iteration, run.average_cps, mods.join(", "));
+    }
+    for line in report(&results) {
+        println!("{}", line);
+    }
+    Ok(())
+}
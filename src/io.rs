@@ -1,120 +1,511 @@
-use crate::benchmark::launch_jar;
-use crate::java::{install_java, java_installed};
-use crate::BenchmarkingStatus;
+use crate::benchmark::{launch_jar, LaunchOptions};
+use crate::java::{install_java, java_bin, java_installed};
+use crate::manifest::{sha256_file, InstanceManifest};
+use crate::settings::Settings;
+use crate::{BenchmarkingStatus, Info};
+use log::{debug, error, info};
+use reqwest::blocking::Client;
 use std::fs::File;
 use std::io::Write;
 use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
 use std::sync::mpsc::Sender;
+use std::sync::OnceLock;
+use std::time::Instant;
 use std::{env, fs, io};
 
 pub const MAIN_DIR: &str = "subchunker";
-pub const SERVER_DIR: &str = "subchunker/server";
-pub const JAVA_DIR: &str = "subchunker/java";
-pub const DATA_DIR: &str = "subchunker/data";
-pub const RUNS_FILE: &str = "subchunker/data/benchmarks.json";
+pub const SERVER_DIR: &str = "server";
+pub const JAVA_DIR: &str = "java";
+pub const DATA_DIR: &str = "data";
+pub const RUNS_FILE: &str = "data/benchmarks.json";
+pub const RUNS_DIR: &str = "data/runs";
+pub const SETTINGS_FILE: &str = "data/settings.json";
+pub const PRESETS_DIR: &str = "data/presets";
+pub const MOD_CATALOG_FILE: &str = "data/mods.json";
+pub const HOME_ENV: &str = "SUBCHUNKER_HOME";
+/// Modpack combo entry for plain instances
+pub const NO_MODPACK: &str = "None";
+pub const EULA_URL: &str = "https://www.minecraft.net/en-us/eula";
+pub const FABRIC_INSTALLER_VERSION: &str = "1.1.0";
+pub const QUILT_INSTALLER_VERSION: &str = "0.9.2";
+
+static HOME: OnceLock<PathBuf> = OnceLock::new();
+
+/// Resolves the data root: `--data-dir`, then `SUBCHUNKER_HOME`, then the platform's data directory
+pub fn init_main_dir(data_dir_arg: Option<PathBuf>) {
+    let home = data_dir_arg
+        .or_else(|| env::var_os(HOME_ENV).map(PathBuf::from))
+        .or_else(|| dirs::data_dir().map(|dir| dir.join(MAIN_DIR)))
+        .unwrap_or_else(legacy_main_dir);
+    HOME.set(home).ok();
+}
+
 pub fn first_time_setup() {
-    if fs::exists(MAIN_DIR).unwrap_or(false) {
-        // Not the first startup
+    migrate_legacy_main_dir();
+
+    // Create folders
+    fs::create_dir_all(main_dir()).unwrap();
+    fs::create_dir_all(server_dir()).unwrap();
+    fs::create_dir_all(data_dir()).unwrap();
+    fs::create_dir_all(java_dir()).unwrap();
+    fs::create_dir_all(presets_dir()).unwrap();
+}
+
+/// Older versions kept everything in `./subchunker`; move it once into the resolved data root
+fn migrate_legacy_main_dir() {
+    let legacy = legacy_main_dir();
+    let home = main_dir();
+    if !legacy.is_dir() || home.exists() || home == legacy {
         return;
     }
 
-    // Create folders
-    fs::create_dir(MAIN_DIR).unwrap();
-    fs::create_dir(SERVER_DIR).unwrap();
-    fs::create_dir(DATA_DIR).unwrap();
-    fs::create_dir(JAVA_DIR).unwrap();
+    info!("Moving {} to {}", legacy.display(), home.display());
+    if let Some(parent) = home.parent() {
+        fs::create_dir_all(parent).unwrap();
+    }
+    // A rename fails across filesystems, so fall back to copying
+    if fs::rename(&legacy, &home).is_err() {
+        match copy_dir(&legacy, &home) {
+            Ok(()) => fs::remove_dir_all(&legacy).unwrap(),
+            Err(e) => error!("Failed to migrate {}: {}", legacy.display(), e),
+        }
+    }
+}
+
+fn copy_dir(from: &Path, to: &Path) -> io::Result<()> {
+    fs::create_dir_all(to)?;
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        let target = to.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_dir(&entry.path(), &target)?;
+        } else {
+            fs::copy(entry.path(), target)?;
+        }
+    }
+    Ok(())
 }
 
-fn working_dir() -> PathBuf {
-    env::current_dir().unwrap()
+fn legacy_main_dir() -> PathBuf {
+    env::current_dir().unwrap().join(MAIN_DIR)
 }
 
 pub fn main_dir() -> PathBuf {
-    working_dir().join(MAIN_DIR)
+    HOME.get().cloned().unwrap_or_else(legacy_main_dir)
 }
 
 pub fn server_dir() -> PathBuf {
-    working_dir().join(SERVER_DIR)
+    main_dir().join(SERVER_DIR)
 }
 
 pub fn data_dir() -> PathBuf {
-    working_dir().join(DATA_DIR)
+    main_dir().join(DATA_DIR)
 }
 pub fn java_dir() -> PathBuf {
-    working_dir().join(JAVA_DIR)
+    main_dir().join(JAVA_DIR)
 }
 
-pub enum InstallerMsg {
-    Progress(f32),
-    Status(BenchmarkingStatus),
-    InstallingMsg(String),
-    Error(String),
+pub fn settings_file() -> PathBuf {
+    main_dir().join(SETTINGS_FILE)
 }
 
-// Installing
-pub fn install_fabric_server(mc_ver: &str, fabric_ver: &str, jvm: &str, ram: u32, sender: &Sender<InstallerMsg>) -> io::Result<()> {
-    // Install MC
-    if !mc_ver_installed(mc_ver.to_string()) {
-        sender.send(InstallerMsg::InstallingMsg(format!("Installing Minecraft {}", mc_ver))).ok();
-        let url = format!(
-            "https://meta.fabricmc.net/v2/versions/loader/{}/{}/1.1.0/server/jar",
-            mc_ver, fabric_ver
-        );
+pub fn presets_dir() -> PathBuf {
+    main_dir().join(PRESETS_DIR)
+}
+
+pub fn mod_catalog_file() -> PathBuf {
+    main_dir().join(MOD_CATALOG_FILE)
+}
 
-        let response = reqwest::blocking::get(&url)
-            .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+pub fn runs_file() -> PathBuf {
+    main_dir().join(RUNS_FILE)
+}
+
+/// Files belonging to a single run, such as its flight recording
+pub fn run_dir(id: &str) -> PathBuf {
+    main_dir().join(RUNS_DIR).join(id)
+}
+
+/// Server software that can be installed and benchmarked
+#[derive(Clone, Copy, PartialEq)]
+pub enum Platform {
+    Fabric,
+    Quilt,
+    NeoForge,
+    Paper,
+    Purpur,
+    Folia,
+}
 
-        if !response.status().is_success() {
-            println!("Download failed");
-            return Err(io::Error::new(
-                io::ErrorKind::Other,
-                format!("Download failed: {}", response.status()),
-            ));
+impl Platform {
+    pub const ALL: [Platform; 6] = [Platform::Fabric, Platform::Quilt, Platform::NeoForge,
+                                    Platform::Paper, Platform::Purpur, Platform::Folia];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Platform::Fabric => "Fabric",
+            Platform::Quilt => "Quilt",
+            Platform::NeoForge => "NeoForge",
+            Platform::Paper => "Paper",
+            Platform::Purpur => "Purpur",
+            Platform::Folia => "Folia",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Platform> {
+        Platform::ALL.into_iter().find(|p| p.name() == name)
+    }
+
+    /// Loader name used by Modrinth's version filter
+    pub fn modrinth_loader(&self) -> &'static str {
+        match self {
+            Platform::Fabric => "fabric",
+            Platform::Quilt => "quilt",
+            Platform::NeoForge => "neoforge",
+            Platform::Paper => "paper",
+            Platform::Purpur => "purpur",
+            Platform::Folia => "folia",
         }
+    }
 
-        fs::create_dir(server_dir().join(mc_ver))?;
+    pub fn installer_version(&self) -> &'static str {
+        match self {
+            Platform::Fabric => FABRIC_INSTALLER_VERSION,
+            Platform::Quilt => QUILT_INSTALLER_VERSION,
+            _ => "",
+        }
+    }
 
-        let output_path = server_dir().join(mc_ver).join("fabric-server.jar");
-        let mut file = File::create(output_path)?;
+    /// Whether the platform loads mods (as opposed to Bukkit plugins)
+    pub fn supports_mods(&self) -> bool {
+        matches!(self, Platform::Fabric | Platform::Quilt | Platform::NeoForge)
+    }
 
-        let bytes = response.bytes()
-            .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+    /// Whether the SubChunker Companion mod drives Chunky on this platform.
+    /// Everywhere else Chunky is started through the server console.
+    pub fn has_companion(&self) -> bool {
+        matches!(self, Platform::Fabric | Platform::Quilt)
+    }
+
+    pub fn mods_folder(&self) -> &'static str {
+        if self.supports_mods() {
+            "mods"
+        } else {
+            "plugins"
+        }
+    }
+
+    fn server_jar(&self) -> &'static str {
+        match self {
+            Platform::Fabric => "fabric-server.jar",
+            Platform::Quilt => "quilt-server-launch.jar",
+            _ => "server.jar",
+        }
+    }
+
+    fn paper_project(&self) -> &'static str {
+        match self {
+            Platform::Folia => "folia",
+            _ => "paper",
+        }
+    }
+}
+
+/// A server install, keyed by every version that affects the produced jar
+#[derive(Clone)]
+pub struct ServerInstance {
+    pub platform: Platform,
+    pub mc_ver: String,
+    pub loader_ver: String,
+    pub installer_ver: String,
+    /// Modpack slug for instances imported from an `.mrpack`, empty otherwise
+    pub pack: String,
+}
+
+impl ServerInstance {
+    pub fn new(platform: Platform, mc_ver: &str, loader_ver: &str) -> Self {
+        Self {
+            platform,
+            mc_ver: mc_ver.to_string(),
+            loader_ver: loader_ver.to_string(),
+            installer_ver: platform.installer_version().to_string(),
+            pack: String::new(),
+        }
+    }
+
+    /// The instance currently selected in the Configure panel
+    pub fn from_info(info: &Info) -> Self {
+        let platform = Platform::from_name(&info.get_selected_platform()).unwrap_or(Platform::Fabric);
+        let mut instance = Self::new(platform, &info.get_selected_minecraft_version(), &info.get_selected_loader_version());
+        if info.get_selected_modpack() != NO_MODPACK {
+            instance.pack = info.get_selected_modpack().to_string();
+        }
+        instance
+    }
+
+    /// Folder name inside `server_dir()`, e.g. `1.21.4-fabric-0.16.10-1.1.0`, `1.21.4-paper-232`
+    /// or `1.21.4-fabric-0.16.10-1.1.0-my_pack` for a modpack
+    pub fn name(&self) -> String {
+        let mut name = format!("{}-{}-{}", self.mc_ver, self.platform.modrinth_loader(), self.loader_ver);
+        if !self.installer_ver.is_empty() {
+            name = format!("{}-{}", name, self.installer_ver);
+        }
+        if !self.pack.is_empty() {
+            name = format!("{}-{}", name, self.pack);
+        }
+        name
+    }
+
+    pub fn dir(&self) -> PathBuf {
+        server_dir().join(self.name())
+    }
+
+    pub fn mods_dir(&self) -> PathBuf {
+        self.dir().join(self.platform.mods_folder())
+    }
+
+    /// Where unselected jars wait while a run doesn't use them, e.g. `mods.disabled`
+    pub fn disabled_mods_dir(&self) -> PathBuf {
+        self.dir().join(format!("{}.disabled", self.platform.mods_folder()))
+    }
+
+    /// The file the server is started from, hashed into the instance manifest
+    pub fn launch_file(&self) -> PathBuf {
+        match self.platform {
+            Platform::NeoForge => {
+                let args_file = if cfg!(target_os = "windows") { "win_args.txt" } else { "unix_args.txt" };
+                self.dir()
+                    .join("libraries/net/neoforged/neoforge")
+                    .join(&self.loader_ver)
+                    .join(args_file)
+            }
+            _ => self.dir().join(self.platform.server_jar()),
+        }
+    }
+
+    /// Arguments that follow the JVM flags when launching the server
+    pub fn launch_args(&self) -> Vec<String> {
+        let mut args = match self.platform {
+            Platform::NeoForge => {
+                let args_file = if cfg!(target_os = "windows") { "win_args.txt" } else { "unix_args.txt" };
+                vec![format!("@libraries/net/neoforged/neoforge/{}/{}", self.loader_ver, args_file)]
+            }
+            _ => vec!["-jar".to_string(), self.platform.server_jar().to_string()],
+        };
+        args.push("nogui".to_string());
+        args
+    }
+
+    /// Worlds generated by the server, removed after every run
+    pub fn world_dirs(&self) -> Vec<PathBuf> {
+        ["world", "world_nether", "world_the_end"]
+            .iter()
+            .map(|world| self.dir().join(world))
+            .collect()
+    }
+}
+
+pub enum InstallerMsg {
+    Progress(f32),
+    Status(BenchmarkingStatus),
+    InstallingMsg(String),
+    Error(String),
+}
 
-        file.write_all(&bytes)?;
+// Installing
+/// Installs the instance, or repairs it by re-running only the steps its manifest reports missing
+pub fn install_server(instance: &ServerInstance, jvm: &str, memory_mb: u32, sender: &Sender<InstallerMsg>) -> io::Result<()> {
+    if !Settings::load().eula_accepted {
+        return Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            "The Minecraft EULA has not been accepted",
+        ));
     }
-    sender.send(InstallerMsg::Progress(0.25)).ok();
 
-    // Install Java
+    info!("Installing {} with {} into {}", instance.name(), jvm, instance.dir().display());
+    let started = Instant::now();
 
+    // Install Java, needed first by installer based platforms
     if !java_installed(jvm) {
         sender.send(InstallerMsg::InstallingMsg(format!("Installing {} JVM", jvm))).ok();
         install_java(jvm)?;
     }
+    sender.send(InstallerMsg::Progress(0.4)).ok();
+
+    let mut manifest = InstanceManifest::load(instance);
+    let health = manifest.verify(instance);
+    debug!("Instance health: jar {}, libraries {}", health.jar, health.libraries);
+
+    // Install MC
+    if !health.jar {
+        sender.send(InstallerMsg::InstallingMsg(format!("Installing {} {}", instance.platform.name(), instance.mc_ver))).ok();
+        fs::create_dir_all(instance.dir())?;
+        download_server(instance, jvm)?;
+        manifest.jar_sha256 = sha256_file(&instance.launch_file())?;
+        manifest.libraries_ready = false;
+        manifest.save(instance)?;
+    }
     sender.send(InstallerMsg::Progress(0.65)).ok();
 
     // Run until EULA
-    if !eula_exists(mc_ver.to_string()) {
+    if !manifest.libraries_ready || !health.libraries {
         sender.send(InstallerMsg::InstallingMsg("Installing Minecraft Libraries".to_string())).ok();
-        launch_jar(mc_ver.to_string(), jvm.to_string(), ram, vec![], None);
-        write_eula(mc_ver.to_string());
+        info!("Running the server once to install its libraries");
+        // An accepted EULA would let the server start for real instead of stopping at the prompt
+        if eula_exists(instance) {
+            fs::remove_file(instance.dir().join("eula.txt"))?;
+        }
+        let options = LaunchOptions { jvm: jvm.to_string(), memory_mb, ..Default::default() };
+        launch_jar(instance, &options, None);
+        manifest.libraries_ready = eula_exists(instance);
+        manifest.save(instance)?;
+        if !manifest.libraries_ready {
+            return Err(io::Error::other("Server stopped before reaching the EULA prompt"));
+        }
+    }
+    sender.send(InstallerMsg::Progress(0.9)).ok();
+
+    if !eula_accepted(instance) {
+        write_eula(instance);
     }
+    manifest.eula_accepted = eula_accepted(instance);
+    manifest.save(instance)?;
 
+    info!("Installed {} in {:.1}s", instance.name(), started.elapsed().as_secs_f64());
     sender.send(InstallerMsg::Progress(1.0)).ok();
     Ok(())
 }
 
-
-// Info functions
-fn installed_minecraft_versions() -> Vec<String> {
-    let mut output: Vec<String> = Vec::new();
-    for entry in server_dir().read_dir().expect("Failed to read server dir") {
-        if let Ok(entry) = entry {
-            output.push(entry.file_name().to_str().unwrap().to_string());
+fn download_server(instance: &ServerInstance, jvm: &str) -> io::Result<()> {
+    let dir = instance.dir();
+    let jar_path = dir.join(instance.platform.server_jar());
+    match instance.platform {
+        Platform::Fabric => {
+            let url = format!(
+                "https://meta.fabricmc.net/v2/versions/loader/{}/{}/{}/server/jar",
+                instance.mc_ver, instance.loader_ver, instance.installer_ver
+            );
+            download_file(&url, &jar_path)
+        }
+        Platform::Quilt => {
+            let url = format!(
+                "https://maven.quiltmc.org/repository/release/org/quiltmc/quilt-installer/{0}/quilt-installer-{0}.jar",
+                instance.installer_ver
+            );
+            let installer = dir.join("quilt-installer.jar");
+            download_file(&url, &installer)?;
+            run_installer(jvm, &dir, vec![
+                "-jar".to_string(),
+                "quilt-installer.jar".to_string(),
+                "install".to_string(),
+                "server".to_string(),
+                instance.mc_ver.clone(),
+                instance.loader_ver.clone(),
+                "--download-server".to_string(),
+                "--install-dir=.".to_string(),
+            ])?;
+            fs::remove_file(installer)
+        }
+        Platform::NeoForge => {
+            let url = format!(
+                "https://maven.neoforged.net/releases/net/neoforged/neoforge/{0}/neoforge-{0}-installer.jar",
+                instance.loader_ver
+            );
+            let installer = dir.join("neoforge-installer.jar");
+            download_file(&url, &installer)?;
+            run_installer(jvm, &dir, vec![
+                "-jar".to_string(),
+                "neoforge-installer.jar".to_string(),
+                "--installServer".to_string(),
+            ])?;
+            fs::remove_file(installer)
+        }
+        Platform::Paper | Platform::Folia => {
+            let url = format!(
+                "https://fill.papermc.io/v3/projects/{}/versions/{}/builds/{}",
+                instance.platform.paper_project(), instance.mc_ver, instance.loader_ver
+            );
+            let build = get_json(&url)?;
+            let jar_url = build["downloads"]["server:default"]["url"]
+                .as_str()
+                .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Build has no server download"))?;
+            download_file(jar_url, &jar_path)
+        }
+        Platform::Purpur => {
+            let url = format!(
+                "https://api.purpurmc.org/v2/purpur/{}/{}/download",
+                instance.mc_ver, instance.loader_ver
+            );
+            download_file(&url, &jar_path)
         }
     }
-    output
 }
 
+fn http_client() -> io::Result<Client> {
+    // PaperMC's API rejects requests without a user agent
+    Client::builder()
+        .user_agent(concat!("SubChunker/", env!("CARGO_PKG_VERSION")))
+        .build()
+        .map_err(io::Error::other)
+}
+
+fn get_json(url: &str) -> io::Result<serde_json::Value> {
+    debug!("Requesting {}", url);
+    let response = http_client()?
+        .get(url)
+        .send()
+        .map_err(io::Error::other)?;
+
+    if !response.status().is_success() {
+        error!("Request to {} failed: {}", url, response.status());
+        return Err(io::Error::other(format!("API request failed: {}", response.status())));
+    }
+
+    response.json().map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+pub fn download_file(url: &str, output_path: &Path) -> io::Result<()> {
+    info!("Downloading {} to {}", url, output_path.display());
+    let started = Instant::now();
+    let response = http_client()?
+        .get(url)
+        .send()
+        .map_err(io::Error::other)?;
+
+    if !response.status().is_success() {
+        error!("Download of {} failed: {}", url, response.status());
+        return Err(io::Error::other(format!("Download failed: {}", response.status())));
+    }
+
+    let mut file = File::create(output_path)?;
+
+    let bytes = response.bytes()
+        .map_err(io::Error::other)?;
+
+    file.write_all(&bytes)?;
+    debug!("Downloaded {} bytes in {:.1}s", bytes.len(), started.elapsed().as_secs_f64());
+    Ok(())
+}
+
+fn run_installer(jvm: &str, dir: &Path, args: Vec<String>) -> io::Result<()> {
+    info!("Running installer in {}: {}", dir.display(), args.join(" "));
+    let started = Instant::now();
+    let status = Command::new(java_bin(jvm))
+        .args(args)
+        .current_dir(dir)
+        .stdin(Stdio::null())
+        .status()?;
+
+    if !status.success() {
+        error!("Installer exited with {} after {:.1}s", status, started.elapsed().as_secs_f64());
+        return Err(io::Error::other(format!("Installer exited with {}", status)));
+    }
+    debug!("Installer finished in {:.1}s", started.elapsed().as_secs_f64());
+    Ok(())
+}
+
+// Info functions
 pub fn get_minecraft_versions() -> Vec<String> {
     let url: &str = "https://meta.fabricmc.net/v2/versions/game";
     let versions = reqwest::blocking::get(url).unwrap();
@@ -130,33 +521,143 @@ pub fn get_minecraft_versions() -> Vec<String> {
     output
 }
 
-pub fn get_fabric_loader_versions() -> Vec<String> {
-    let url: &str = "https://meta.fabricmc.net/v2/versions/loader";
-    let versions = reqwest::blocking::get(url).unwrap();
-    let json: serde_json::Value = versions.json().unwrap();
+/// Loader versions (or build numbers for Paper-like platforms) for a Minecraft version, newest first
+pub fn get_loader_versions(platform: Platform, mc_ver: &str) -> io::Result<Vec<String>> {
     let mut output: Vec<String> = Vec::new();
-    for version in json.as_array().unwrap() {
-        let stable: bool = version["stable"].as_bool().unwrap();
-        if stable {
-            let version = version["version"].as_str().unwrap();
-            output.push(version.to_string());
+    match platform {
+        Platform::Fabric => {
+            let json = get_json("https://meta.fabricmc.net/v2/versions/loader")?;
+            for version in json.as_array().into_iter().flatten() {
+                if version["stable"].as_bool().unwrap_or(false)
+                    && let Some(version) = version["version"].as_str()
+                {
+                    output.push(version.to_string());
+                }
+            }
+        }
+        Platform::Quilt => {
+            let json = get_json("https://meta.quiltmc.org/v3/versions/loader")?;
+            for version in json.as_array().into_iter().flatten() {
+                if let Some(version) = version["version"].as_str() {
+                    // Pre-releases carry a suffix such as `-beta.3`
+                    if !version.contains('-') {
+                        output.push(version.to_string());
+                    }
+                }
+            }
+        }
+        Platform::NeoForge => {
+            let json = get_json("https://maven.neoforged.net/api/maven/versions/releases/net/neoforged/neoforge")?;
+            // NeoForge drops the leading `1.` of the Minecraft version: 1.21.4 -> 21.4.x, 1.21 -> 21.0.x
+            let mut parts = mc_ver.split('.').skip(1);
+            let prefix = format!("{}.{}.", parts.next().unwrap_or("0"), parts.next().unwrap_or("0"));
+            for version in json["versions"].as_array().into_iter().flatten().rev() {
+                if let Some(version) = version.as_str()
+                    && version.starts_with(&prefix) && !version.contains('-')
+                {
+                    output.push(version.to_string());
+                }
+            }
+        }
+        Platform::Paper | Platform::Folia => {
+            let url = format!(
+                "https://fill.papermc.io/v3/projects/{}/versions/{}/builds",
+                platform.paper_project(), mc_ver
+            );
+            let json = get_json(&url)?;
+            let mut builds: Vec<i64> = Vec::new();
+            for build in json.as_array().into_iter().flatten() {
+                let channel = build["channel"].as_str().unwrap_or("");
+                if (channel == "STABLE" || channel == "RECOMMENDED")
+                    && let Some(id) = build["id"].as_i64()
+                {
+                    builds.push(id);
+                }
+            }
+            builds.sort_unstable_by(|a, b| b.cmp(a));
+            output.extend(builds.iter().map(|id| id.to_string()));
+        }
+        Platform::Purpur => {
+            let json = get_json(&format!("https://api.purpurmc.org/v2/purpur/{}", mc_ver))?;
+            for build in json["builds"]["all"].as_array().into_iter().flatten().rev() {
+                if let Some(build) = build.as_str() {
+                    output.push(build.to_string());
+                }
+            }
         }
     }
-    output
+    Ok(output)
+}
+
+/// Sets a key in `server.properties`, creating the file when the server hasn't written it yet
+pub fn set_server_property(instance: &ServerInstance, key: &str, value: &str) -> io::Result<()> {
+    let path = instance.dir().join("server.properties");
+    let contents = fs::read_to_string(&path).unwrap_or_default();
+
+    let prefix = format!("{}=", key);
+    let mut found = false;
+    let mut lines: Vec<String> = contents
+        .lines()
+        .map(|line| {
+            if line.starts_with(&prefix) {
+                found = true;
+                format!("{}{}", prefix, value)
+            } else {
+                line.to_string()
+            }
+        })
+        .collect();
+    if !found {
+        lines.push(format!("{}{}", prefix, value));
+    }
+
+    fs::write(path, lines.join("\n"))
+}
+
+/// Sets C2ME's worker thread count in `config/c2me.toml`, or puts it back to its own default
+pub fn set_c2me_threads(instance: &ServerInstance, threads: Option<u32>) -> io::Result<()> {
+    let path = instance.dir().join("config").join("c2me.toml");
+    if threads.is_none() && !fs::exists(&path)? {
+        return Ok(());
+    }
+    let contents = fs::read_to_string(&path).unwrap_or_default();
+
+    let key = "globalExecutorParallelism";
+    let value = threads.map_or("\"default\"".to_string(), |threads| threads.to_string());
+    let mut found = false;
+    let mut lines: Vec<String> = contents
+        .lines()
+        .map(|line| {
+            if line.trim_start().starts_with(key) {
+                found = true;
+                format!("{} = {}", key, value)
+            } else {
+                line.to_string()
+            }
+        })
+        .collect();
+    if !found {
+        // Top level keys have to come before the first table
+        lines.insert(0, format!("{} = {}", key, value));
+    }
+
+    fs::create_dir_all(instance.dir().join("config"))?;
+    fs::write(path, lines.join("\n"))
 }
 
-pub fn mc_ver_installed(version: String) -> bool {
-    let installed_minecraft_versions = installed_minecraft_versions();
-    installed_minecraft_versions.contains(&version)
+fn eula_exists(instance: &ServerInstance) -> bool {
+    fs::exists(instance.dir().join("eula.txt")).unwrap()
 }
 
-fn eula_exists(version: String) -> bool {
-    fs::exists(server_dir().join(&*version).join("eula.txt")).unwrap()
+pub fn eula_accepted(instance: &ServerInstance) -> bool {
+    fs::read_to_string(instance.dir().join("eula.txt"))
+        .is_ok_and(|contents| contents.lines().any(|line| line.trim() == "eula=true"))
 }
 
-fn write_eula(version: String) {
-    if eula_exists(version.clone()) {
-        accept_minecraft_eula(&*server_dir().join(&*version).join("eula.txt")).unwrap();
+/// Only reached from `install_server`, after the user consented in `Settings`
+fn write_eula(instance: &ServerInstance) {
+    if eula_exists(instance) {
+        accept_minecraft_eula(&instance.dir().join("eula.txt")).unwrap();
     }
 }
 
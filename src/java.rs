@@ -2,8 +2,11 @@ use std::{fs, io};
 use std::fs::File;
 use std::io::Write;
 use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::Instant;
+use log::{debug, info};
 use reqwest::blocking::Client;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use zip::ZipArchive;
 use crate::io::java_dir;
 
@@ -12,28 +15,102 @@ pub fn java_installed(distro: &str) -> bool {
     fs::exists(java_dir().join(&*distro.to_lowercase())).unwrap_or(false)
 }
 
+pub fn java_bin(distro: &str) -> PathBuf {
+    let bin = java_dir().join(distro.to_lowercase()).join("bin");
+    if cfg!(target_os = "windows") {
+        bin.join("javaw.exe")
+    } else {
+        bin.join("java")
+    }
+}
+
+/// The JDK's flight recording tool
+pub fn jfr_bin(distro: &str) -> PathBuf {
+    let bin = java_dir().join(distro.to_lowercase()).join("bin");
+    if cfg!(target_os = "windows") {
+        bin.join("jfr.exe")
+    } else {
+        bin.join("jfr")
+    }
+}
+
+/// Flag selecting a garbage collector from the Configure panel
+pub fn gc_flag(gc: &str) -> Option<String> {
+    match gc {
+        "Z" => Some("-XX:+UseZGC".to_string()),
+        "Shenandoah" => Some("-XX:+UseShenandoahGC".to_string()),
+        "G1" => Some("-XX:+UseG1GC".to_string()),
+        "Parallel" => Some("-XX:+UseParallelGC".to_string()),
+        _ => None,
+    }
+}
+
+/// Flags for the JVM arguments from the Configure panel
+pub fn arg_flags(args: &[String]) -> Vec<String> {
+    args.iter()
+        .map(|arg| match arg.as_str() {
+            "LargePages" => "-XX:+UseLargePages".to_string(),
+            _ => format!("-XX:+{}", arg),
+        })
+        .collect()
+}
+
+/// GC worker threads, keeping HotSpot's default of one concurrent thread per four parallel ones
+pub fn gc_thread_flags(threads: u32) -> Vec<String> {
+    vec![
+        format!("-XX:ParallelGCThreads={}", threads),
+        format!("-XX:ConcGCThreads={}", threads.div_ceil(4)),
+    ]
+}
+
+/// The heap the JVM actually settles on, which may be rounded from the requested `-Xmx`
+#[derive(Serialize, Deserialize, Clone)]
+pub struct JvmHeap {
+    pub max_heap_mb: u64,
+    pub compressed_oops: bool,
+}
+
+/// Asks the JVM for its final `MaxHeapSize` and `UseCompressedOops` under the given flags
+pub fn jvm_heap(distro: &str, memory_mb: u32, args: &[String]) -> Option<JvmHeap> {
+    let output = Command::new(java_bin(distro))
+        .arg(format!("-Xmx{}M", memory_mb))
+        .args(args)
+        .args(["-XX:+PrintFlagsFinal", "-version"])
+        .output()
+        .ok()?;
+    let flags = String::from_utf8_lossy(&output.stdout);
+    // Lines look like `size_t MaxHeapSize = 4294967296 {product} {ergonomic}`
+    let flag = |name: &str| {
+        flags.lines().find_map(|line| {
+            let mut fields = line.split_whitespace().skip(1);
+            (fields.next()? == name && fields.next()? == "=").then(|| fields.next().map(str::to_string))?
+        })
+    };
+    Some(JvmHeap {
+        max_heap_mb: flag("MaxHeapSize")?.parse::<u64>().ok()? / 1_048_576,
+        compressed_oops: flag("UseCompressedOops").is_some_and(|value| value == "true"),
+    })
+}
+
 pub fn install_java(distro: &str) -> io::Result<()> {
     let url = match distro {
         "Azul" => {
             let api_url = azul_url();
-            println!("Requesting Azul API JSON from {}", api_url);
+            debug!("Requesting Azul API JSON from {}", api_url);
 
             let client = Client::new();
             let resp = client
                 .get(&api_url)
                 .send()
-                .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+                .map_err(io::Error::other)?;
 
             if !resp.status().is_success() {
-                return Err(io::Error::new(
-                    io::ErrorKind::Other,
-                    format!("API request failed: {}", resp.status()),
-                ));
+                return Err(io::Error::other(format!("API request failed: {}", resp.status())));
             }
 
             let azul_json: AzulJson = resp
                 .json()
-                .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+                .map_err(io::Error::other)?;
 
             azul_json.url
         }
@@ -42,28 +119,27 @@ pub fn install_java(distro: &str) -> io::Result<()> {
         _ => panic!("Unknown JVM distro"),
     };
 
-    println!("Downloading {}", url);
+    info!("Downloading {} JVM from {}", distro, url);
+    let started = Instant::now();
 
     let response = reqwest::blocking::get(&url)
-        .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+        .map_err(io::Error::other)?;
 
     let bytes = response
         .bytes()
-        .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+        .map_err(io::Error::other)?;
 
     let filename = format!("{}/{}.zip", java_dir().to_str().unwrap(), distro.to_lowercase());
-    println!("Saving to {}", filename);
+    info!("Saving {} bytes to {} after {:.1}s", bytes.len(), filename, started.elapsed().as_secs_f64());
     let mut file = File::create(&filename)?;
     file.write_all(&bytes)?;
 
-    println!(
-        "Extracting {}",
-        java_dir().join(distro.to_lowercase()).to_str().unwrap()
-    );
+    info!("Extracting to {}", java_dir().join(distro.to_lowercase()).display());
     extract_zip(
         Path::new(&filename),
         &java_dir().join(distro.to_lowercase()),
     )?;
+    info!("Installed {} JVM in {:.1}s", distro, started.elapsed().as_secs_f64());
 
     Ok(())
 }
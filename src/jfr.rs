@@ -0,0 +1,201 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, BufReader};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use crate::io::{run_dir, ServerInstance};
+use crate::java::jfr_bin;
+
+// Relative to the instance folder like the GC log, Windows drive letters would clash with
+// the option syntax
+const RECORDING: &str = "profile.jfr";
+const SAMPLE_EVENTS: &str = "jdk.ExecutionSample,jdk.NativeMethodSample,jdk.ObjectAllocationSample";
+const TOP_ENTRIES: usize = 5;
+
+/// JVM flag recording the run with Java Flight Recorder. The default settings keep the
+/// overhead around one percent, so the CPS stay comparable to unprofiled runs.
+pub fn jfr_flag() -> String {
+    format!("-XX:StartFlightRecording=filename={},dumponexit=true", RECORDING)
+}
+
+pub fn recording_file(instance: &ServerInstance) -> PathBuf {
+    instance.dir().join(RECORDING)
+}
+
+/// Moves the instance's recording to `data/runs/<id>/profile.jfr`
+pub fn keep_recording(instance: &ServerInstance, run_id: &str) -> io::Result<PathBuf> {
+    let dir = run_dir(run_id);
+    fs::create_dir_all(&dir)?;
+    let path = dir.join(RECORDING);
+    fs::copy(recording_file(instance), &path)?;
+    fs::remove_file(recording_file(instance))?;
+    Ok(path)
+}
+
+/// Where the run's CPU time and allocations went, from a flight recording
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct JfrSummary {
+    /// Methods on top of the most execution samples with their share in percent, hottest first
+    pub hot_methods: Vec<(String, f64)>,
+    /// Allocated MB as estimated by the allocation samples
+    pub allocated_mb: f64,
+    pub duration_secs: f64,
+    /// Classes with the most allocated MB
+    pub top_allocations: Vec<(String, f64)>,
+    /// Execution and native method samples per thread state, most common first
+    pub thread_states: Vec<(String, u32)>,
+}
+
+#[derive(Deserialize)]
+struct JfrPrint {
+    recording: JfrRecording,
+}
+
+#[derive(Deserialize)]
+struct JfrRecording {
+    events: Vec<JfrEvent>,
+}
+
+#[derive(Deserialize)]
+struct JfrEvent {
+    #[serde(rename = "type")]
+    kind: String,
+    values: JfrValues,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default, rename_all = "camelCase")]
+struct JfrValues {
+    state: Value,
+    stack_trace: Option<JfrStackTrace>,
+    object_class: Option<JfrClass>,
+    weight: u64,
+}
+
+#[derive(Deserialize)]
+struct JfrStackTrace {
+    frames: Vec<JfrFrame>,
+}
+
+#[derive(Deserialize)]
+struct JfrFrame {
+    method: JfrMethod,
+}
+
+#[derive(Deserialize)]
+struct JfrMethod {
+    #[serde(rename = "type")]
+    class: JfrClass,
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct JfrClass {
+    name: String,
+}
+
+impl JfrSummary {
+    /// Summarises `recording` with the JDK's `jfr print --json`. Only the top frame of each
+    /// sample is printed, full stacks of a long run would take gigabytes.
+    pub fn read(distro: &str, recording: &Path, duration_secs: f64) -> io::Result<JfrSummary> {
+        let mut child = Command::new(jfr_bin(distro))
+            .args(["print", "--json", "--stack-depth", "1", "--events", SAMPLE_EVENTS])
+            .arg(recording)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()?;
+        let stdout = child.stdout.take().ok_or_else(|| io::Error::other("jfr has no output"))?;
+        let print: Result<JfrPrint, _> = serde_json::from_reader(BufReader::new(stdout));
+        let status = child.wait()?;
+        if !status.success() {
+            return Err(io::Error::other(format!("jfr print failed with {}", status)));
+        }
+        let print = print.map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        Ok(JfrSummary::from_events(&print.recording.events, duration_secs))
+    }
+
+    fn from_events(events: &[JfrEvent], duration_secs: f64) -> JfrSummary {
+        let mut methods: HashMap<String, u32> = HashMap::new();
+        let mut execution_samples = 0;
+        let mut allocations: HashMap<String, u64> = HashMap::new();
+        let mut states: HashMap<String, u32> = HashMap::new();
+        for event in events {
+            match event.kind.as_str() {
+                "jdk.ExecutionSample" | "jdk.NativeMethodSample" => {
+                    // Constants print either as a plain string or as an object with a name
+                    let state = event.values.state.as_str().or(event.values.state["name"].as_str());
+                    if let Some(state) = state {
+                        let state = state.trim_start_matches("STATE_").replace('_', " ").to_lowercase();
+                        *states.entry(state).or_default() += 1;
+                    }
+                    if event.kind != "jdk.ExecutionSample" {
+                        continue;
+                    }
+                    execution_samples += 1;
+                    let top = event.values.stack_trace.as_ref().and_then(|trace| trace.frames.first());
+                    if let Some(frame) = top {
+                        let method = format!("{}.{}", class_name(&frame.method.class), frame.method.name);
+                        *methods.entry(method).or_default() += 1;
+                    }
+                }
+                "jdk.ObjectAllocationSample" => {
+                    if let Some(class) = &event.values.object_class {
+                        *allocations.entry(class_name(class)).or_default() += event.values.weight;
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let hot_methods = top_entries(methods)
+            .into_iter()
+            .map(|(method, count)| (method, count as f64 * 100.0 / execution_samples as f64))
+            .collect();
+        let allocated_mb = allocations.values().sum::<u64>() as f64 / 1_048_576.0;
+        let top_allocations = top_entries(allocations)
+            .into_iter()
+            .map(|(class, bytes)| (class, bytes as f64 / 1_048_576.0))
+            .collect();
+        let mut thread_states: Vec<(String, u32)> = states.into_iter().collect();
+        sort_by_count(&mut thread_states);
+        JfrSummary { hot_methods, allocated_mb, duration_secs, top_allocations, thread_states }
+    }
+
+    /// Lines for the Finished screen
+    pub fn lines(&self) -> Vec<String> {
+        let mut lines = vec!["Hot methods:".to_string()];
+        lines.extend(self.hot_methods.iter().map(|(method, share)| format!("  {:.1}% {}", share, method)));
+        let rate = if self.duration_secs > 0.0 { self.allocated_mb / self.duration_secs } else { 0.0 };
+        lines.push(format!("Allocation: {:.0} MB/s, {:.0} MB in total", rate, self.allocated_mb));
+        lines.extend(self.top_allocations.iter().map(|(class, mb)| format!("  {:.0} MB {}", mb, class)));
+        let total: u32 = self.thread_states.iter().map(|(_, count)| count).sum();
+        if total > 0 {
+            let states: Vec<String> = self
+                .thread_states
+                .iter()
+                .map(|(state, count)| format!("{} {:.0}%", state, *count as f64 * 100.0 / total as f64))
+                .collect();
+            lines.push(format!("Thread states: {}", states.join(", ")));
+        }
+        lines
+    }
+}
+
+// Recordings store binary names such as `java/lang/String`
+fn class_name(class: &JfrClass) -> String {
+    class.name.replace('/', ".")
+}
+
+fn top_entries<T: Ord + Copy>(counts: HashMap<String, T>) -> Vec<(String, T)> {
+    let mut entries: Vec<(String, T)> = counts.into_iter().collect();
+    sort_by_count(&mut entries);
+    entries.truncate(TOP_ENTRIES);
+    entries
+}
+
+// Highest first; ties are broken by name since `HashMap` order changes between runs
+fn sort_by_count<T: Ord>(entries: &mut [(String, T)]) {
+    entries.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+}
@@ -0,0 +1,242 @@
+use std::env;
+use std::io;
+use reqwest::blocking::Client;
+use serde::{Deserialize, Serialize};
+use crate::bundle::ResultBundle;
+use crate::runs::BenchmarkRun;
+use crate::settings::Settings;
+
+const LEADERBOARD_URL_ENV: &str = "SUBCHUNKER_LEADERBOARD_URL";
+
+/// A run as ranked by the leaderboard
+#[derive(Serialize, Deserialize, Clone)]
+pub struct LeaderboardEntry {
+    pub run_id: String,
+    pub cpu: String,
+    pub minecraft_version: String,
+    pub platform: String,
+    /// Label of the run, e.g. the GC or `+Lithium`
+    pub config: String,
+    pub jvm: String,
+    pub average_cps: f32,
+}
+
+/// Narrows the top runs; empty fields match everything
+#[derive(Serialize, Default)]
+pub struct LeaderboardFilter {
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub cpu: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub minecraft_version: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub config: String,
+}
+
+pub trait LeaderboardBackend {
+    fn submit(&self, run: &BenchmarkRun) -> io::Result<()>;
+    /// The fastest runs matching `filter`, best first
+    fn top_runs(&self, filter: &LeaderboardFilter, limit: usize) -> io::Result<Vec<LeaderboardEntry>>;
+}
+
+/// Talks to the leaderboard's REST API: `POST /runs` with a result bundle, `GET /runs/top`
+pub struct HttpLeaderboard {
+    base_url: String,
+    client: Client,
+}
+
+impl HttpLeaderboard {
+    pub fn new(base_url: &str) -> Self {
+        HttpLeaderboard { base_url: base_url.trim_end_matches('/').to_string(), client: Client::new() }
+    }
+
+    /// The leaderboard named by `SUBCHUNKER_LEADERBOARD_URL`, or by `leaderboard_url` in the settings.
+    /// There is no default, runs are only ever sent to a server the user picked.
+    pub fn configured() -> io::Result<Self> {
+        env::var(LEADERBOARD_URL_ENV)
+            .ok()
+            .or(Settings::load().leaderboard_url)
+            .filter(|url| !url.trim().is_empty())
+            .map(|url| HttpLeaderboard::new(url.trim()))
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!("No leaderboard configured, set {} or leaderboard_url in the settings", LEADERBOARD_URL_ENV),
+                )
+            })
+    }
+}
+
+impl LeaderboardBackend for HttpLeaderboard {
+    fn submit(&self, run: &BenchmarkRun) -> io::Result<()> {
+        self.client
+            .post(format!("{}/runs", self.base_url))
+            .json(&ResultBundle::new(run)?)
+            .send()
+            .and_then(|response| response.error_for_status())
+            .map_err(io::Error::other)?;
+        Ok(())
+    }
+
+    fn top_runs(&self, filter: &LeaderboardFilter, limit: usize) -> io::Result<Vec<LeaderboardEntry>> {
+        self.client
+            .get(format!("{}/runs/top", self.base_url))
+            .query(filter)
+            .query(&[("limit", limit)])
+            .send()
+            .and_then(|response| response.error_for_status())
+            .and_then(|response| response.json())
+            .map_err(io::Error::other)
+    }
+}
+
+/// Uploads the runs, refusing unless the user opted in with `consent`. Runs imported from other
+/// machines are skipped. Returns the number of runs submitted.
+pub fn submit_runs(backend: &dyn LeaderboardBackend, runs: &[BenchmarkRun], consent: bool) -> io::Result<usize> {
+    if !consent {
+        return Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            "Uploading to the leaderboard needs your consent in the Configure panel",
+        ));
+    }
+    let own: Vec<&BenchmarkRun> = runs.iter().filter(|run| !run.foreign).collect();
+    for run in &own {
+        backend.submit(run)?;
+    }
+    Ok(own.len())
+}
+
+/// One line per entry: `1. 512.3 CPS · G1 · Fabric 1.21.4 · CPU`
+pub fn leaderboard_report(entries: &[LeaderboardEntry]) -> Vec<String> {
+    if entries.is_empty() {
+        return vec!["No runs match the filter".to_string()];
+    }
+    entries
+        .iter()
+        .enumerate()
+        .map(|(i, entry)| {
+            let config = if entry.config.is_empty() { "Default" } else { entry.config.as_str() };
+            format!(
+                "{}. {:.1} CPS · {} · {} {} · {} · {}",
+                i + 1, entry.average_cps, config, entry.platform, entry.minecraft_version, entry.jvm, entry.cpu.trim_end()
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::thread::{self, JoinHandle};
+
+    fn run(id: &str, foreign: bool) -> BenchmarkRun {
+        serde_json::from_value(serde_json::json!({
+            "id": id, "timestamp": 0, "instance": "Fabric 1.21.4", "platform": "Fabric",
+            "minecraft_version": "1.21.4", "loader_version": "0.16.10", "jvm": "Temurin 21",
+            "args": [], "seed": "", "radius": 0, "average_cps": 500.0, "mods": [], "foreign": foreign,
+        }))
+        .unwrap()
+    }
+
+    /// Records the submitted run IDs
+    #[derive(Default)]
+    struct FakeLeaderboard {
+        submitted: RefCell<Vec<String>>,
+    }
+
+    impl LeaderboardBackend for FakeLeaderboard {
+        fn submit(&self, run: &BenchmarkRun) -> io::Result<()> {
+            self.submitted.borrow_mut().push(run.id.clone());
+            Ok(())
+        }
+
+        fn top_runs(&self, _: &LeaderboardFilter, _: usize) -> io::Result<Vec<LeaderboardEntry>> {
+            Ok(Vec::new())
+        }
+    }
+
+    /// Answers one HTTP request with `status` and `body`, returning the request line and body it got
+    fn serve_once(status: &'static str, body: &'static str) -> (String, JoinHandle<(String, String)>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/api/v1/", listener.local_addr().unwrap());
+        let handle = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line).unwrap();
+            let mut length = 0;
+            loop {
+                let mut header = String::new();
+                reader.read_line(&mut header).unwrap();
+                if header.trim().is_empty() {
+                    break;
+                }
+                if let Some((name, value)) = header.split_once(':')
+                    && name.eq_ignore_ascii_case("content-length")
+                {
+                    length = value.trim().parse().unwrap();
+                }
+            }
+            let mut request_body = vec![0; length];
+            reader.read_exact(&mut request_body).unwrap();
+            let response = format!(
+                "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                status, body.len(), body
+            );
+            reader.get_mut().write_all(response.as_bytes()).unwrap();
+            (request_line.trim_end().to_string(), String::from_utf8(request_body).unwrap())
+        });
+        (url, handle)
+    }
+
+    #[test]
+    fn refuses_without_consent() {
+        let backend = FakeLeaderboard::default();
+        let error = submit_runs(&backend, &[run("1", false)], false).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::PermissionDenied);
+        assert!(backend.submitted.borrow().is_empty());
+    }
+
+    #[test]
+    fn skips_foreign_runs() {
+        let backend = FakeLeaderboard::default();
+        let runs = [run("1", false), run("2", true), run("3", false)];
+        assert_eq!(submit_runs(&backend, &runs, true).unwrap(), 2);
+        assert_eq!(*backend.submitted.borrow(), ["1", "3"]);
+    }
+
+    #[test]
+    fn submit() {
+        let (url, server) = serve_once("201 Created", "");
+        HttpLeaderboard::new(&url).submit(&run("42", false)).unwrap();
+        let (request_line, body) = server.join().unwrap();
+        assert_eq!(request_line, "POST /api/v1/runs HTTP/1.1");
+        let bundle: ResultBundle = serde_json::from_str(&body).unwrap();
+        assert_eq!(bundle.verified_run().unwrap().id, "42");
+    }
+
+    #[test]
+    fn submit_error_status() {
+        let (url, server) = serve_once("500 Internal Server Error", "");
+        assert!(HttpLeaderboard::new(&url).submit(&run("42", false)).is_err());
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn top_runs() {
+        let (url, server) = serve_once(
+            "200 OK",
+            r#"[{"run_id":"7","cpu":"Ryzen 7 7800X3D","minecraft_version":"1.21.4","platform":"Fabric","config":"G1","jvm":"Temurin 21","average_cps":612.5}]"#,
+        );
+        let filter = LeaderboardFilter { minecraft_version: "1.21.4".to_string(), config: "G1".to_string(), ..Default::default() };
+        let entries = HttpLeaderboard::new(&url).top_runs(&filter, 10).unwrap();
+        let (request_line, _) = server.join().unwrap();
+        // Empty filter fields are left out of the query
+        assert_eq!(request_line, "GET /api/v1/runs/top?minecraft_version=1.21.4&config=G1&limit=10 HTTP/1.1");
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].run_id, "7");
+        assert_eq!(entries[0].average_cps, 612.5);
+    }
+}
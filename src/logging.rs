@@ -0,0 +1,106 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, PoisonError};
+use std::time::{SystemTime, UNIX_EPOCH};
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use crate::export::format_date;
+use crate::io::main_dir;
+
+pub const LOG_FILE: &str = "data/subchunker.log";
+// The log starts over in a new file past this size, keeping the previous ones as `.1` to `.3`
+const MAX_LOG_BYTES: u64 = 5 * 1024 * 1024;
+const KEPT_LOGS: u32 = 3;
+
+/// Records go to stderr and, once `log_to_file` has been called, to `data/subchunker.log`.
+/// The file gets everything from debug up so it can be attached to bug reports; the
+/// console only shows debug records with `--verbose`. Other crates are limited to warnings.
+struct Logger {
+    verbose: AtomicBool,
+    file: Mutex<Option<LogFile>>,
+}
+
+struct LogFile {
+    file: File,
+    path: PathBuf,
+    size: u64,
+}
+
+static LOGGER: Logger = Logger { verbose: AtomicBool::new(false), file: Mutex::new(None) };
+
+pub fn log_file() -> PathBuf {
+    main_dir().join(LOG_FILE)
+}
+
+pub fn init_logging(verbose: bool) {
+    LOGGER.verbose.store(verbose, Ordering::Relaxed);
+    if log::set_logger(&LOGGER).is_ok() {
+        log::set_max_level(LevelFilter::Debug);
+    }
+}
+
+/// Starts writing the log file. Called once the data directory exists, records before that
+/// only reach the console.
+pub fn log_to_file() {
+    let path = log_file();
+    match LogFile::open(path.clone()) {
+        Ok(file) => *LOGGER.file.lock().unwrap_or_else(PoisonError::into_inner) = Some(file),
+        Err(e) => log::warn!("Failed to open log file {}: {}", path.display(), e),
+    }
+}
+
+impl LogFile {
+    fn open(path: PathBuf) -> io::Result<LogFile> {
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let size = file.metadata()?.len();
+        Ok(LogFile { file, path, size })
+    }
+}
+
+/// Shifts `subchunker.log` to `.1`, `.1` to `.2` and so on, dropping the oldest
+fn rotate(path: &Path) {
+    let numbered = |n: u32| PathBuf::from(format!("{}.{}", path.display(), n));
+    for n in (1..KEPT_LOGS).rev() {
+        fs::rename(numbered(n), numbered(n + 1)).ok();
+    }
+    fs::rename(path, numbered(1)).ok();
+}
+
+impl Log for Logger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        let ours = metadata.target().starts_with(env!("CARGO_PKG_NAME"));
+        metadata.level() <= if ours { Level::Debug } else { Level::Warn }
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let console = if self.verbose.load(Ordering::Relaxed) { Level::Debug } else { Level::Info };
+        if record.level() <= console {
+            eprintln!("[{}] {}", record.level(), record.args());
+        }
+
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+        // `format_date` stops at minutes
+        let line = format!("{}:{:02} {:<5} {}: {}\n", format_date(now), now % 60, record.level(), record.target(), record.args());
+        let mut file = self.file.lock().unwrap_or_else(PoisonError::into_inner);
+        // Windows can't rename an open file, so it is closed first
+        if let Some(path) = file.take_if(|log| log.size + line.len() as u64 > MAX_LOG_BYTES).map(|log| log.path) {
+            rotate(&path);
+            *file = LogFile::open(path).ok();
+        }
+        if let Some(log) = file.as_mut()
+            && log.file.write_all(line.as_bytes()).is_ok()
+        {
+            log.size += line.len() as u64;
+        }
+    }
+
+    fn flush(&self) {
+        if let Some(log) = self.file.lock().unwrap_or_else(PoisonError::into_inner).as_mut() {
+            log.file.flush().ok();
+        }
+    }
+}
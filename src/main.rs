@@ -1,13 +1,27 @@
-use crate::io::{first_time_setup, get_fabric_loader_versions, get_minecraft_versions, install_fabric_server, server_dir, InstallerMsg};
-use crate::mods::{get_mods, get_url, is_mod_installed, MODS, REQ_MODS};
-use crate::slint_utils::{bool_arr_to_rc, string_arr_to_rc, string_vec_to_rc};
+use crate::cli::{CliArgs, Command};
+use crate::compare::{entry_key, set_run_groups};
+use crate::io::{data_dir, first_time_setup, get_loader_versions, init_main_dir, get_minecraft_versions, main_dir, install_server, InstallerMsg, Platform, ServerInstance, EULA_URL, NO_MODPACK};
+use crate::mods::{get_mods, get_url, install_local_mod, is_mod_installed, restore_mods, ModCatalog};
+use crate::slint_utils::{bool_arr_to_rc, checked_names, string_vec_to_rc, toggled_names};
 use crate::system_info::SystemInfo;
 use slint::{Model, SharedString};
 use std::cell::RefCell;
+use std::path::Path;
 use std::rc::Rc;
 use std::sync::mpsc;
 use std::thread;
 use crate::benchmark::start_benchmark;
+use crate::manifest::InstanceManifest;
+use crate::settings::Settings;
+use crate::presets::{preset_names, Preset};
+use crate::bundle::{export_bundle, import_bundle};
+use crate::leaderboard::{leaderboard_report, submit_runs, HttpLeaderboard, LeaderboardBackend, LeaderboardFilter};
+use crate::export::{export_to, parse_since, runs_matching, runs_since, write_export, ExportFormat};
+use crate::memory::{ram_warnings, recommended_ram_mb};
+use crate::mrpack::{import_mrpack, imported_packs};
+use crate::server_log::{filter_log, save_log};
+use crate::logging::{init_logging, log_to_file};
+use log::{error, info, warn};
 
 mod system_info;
 mod io;
@@ -15,21 +29,92 @@ mod slint_utils;
 mod benchmark;
 mod mods;
 mod java;
+mod manifest;
+mod settings;
+mod cli;
+mod presets;
+mod headless;
+mod runs;
+mod versions;
+mod stats;
+mod ablation;
+mod mrpack;
+mod compare;
+mod export;
+mod gc_log;
+mod bundle;
+mod leaderboard;
+mod preflight;
+mod memory;
+mod scaling;
+mod jfr;
+mod server_log;
+mod logging;
 
 slint::include_modules!();
-fn main() {
-    let app = App::new().unwrap();
 
-    let appdata = app.global::<Info>();
+// Entries shown when fetching the leaderboard
+const LEADERBOARD_SIZE: usize = 20;
+
+fn main() {
     // Initial startup
+    let cli = CliArgs::parse();
+    init_logging(cli.verbose);
+    init_main_dir(cli.data_dir.clone());
     first_time_setup();
+    log_to_file();
+    info!("SubChunker {} using {}", env!("CARGO_PKG_VERSION"), main_dir().display());
+
+    // Settings
+    let mut settings = Settings::load();
+    if cli.accept_eula && !settings.eula_accepted {
+        settings.eula_accepted = true;
+        settings.save().unwrap();
+    }
+
+    if let Some(Command::Compare(a, b)) = &cli.command {
+        match compare::compare(a, b) {
+            Ok(lines) => lines.iter().for_each(|line| println!("{}", line)),
+            Err(e) => {
+                error!("Compare failed: {}", e);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
+    if let Some(Command::Export) = &cli.command {
+        if let Err(e) = export_cli(&cli) {
+            error!("Export failed: {}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
 
+    if cli.headless {
+        let Some(preset) = cli.preset else {
+            error!("--headless needs --preset <name or file>");
+            std::process::exit(2);
+        };
+        if let Err(e) = headless::run(&preset, cli.ablation, cli.scaling) {
+            error!("Benchmark failed: {}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    let app = App::new().unwrap();
+
+    let appdata = app.global::<Info>();
+    appdata.set_eula_accepted(settings.eula_accepted);
 
     // Collect system info
     let info = SystemInfo::get();
     if let Ok(info) = info {
         appdata.set_processor(SharedString::from(info.cpu.trim_end()));
         appdata.set_logical_cpu_processors(info.cores);
+        appdata.set_physical_cpu_cores(info.physical_cores);
+        appdata.set_cpu_details(SharedString::from(info.details()));
         appdata.set_memory_capacity_gb(info.memory.round() as i32);
         appdata.set_graphics_processor(SharedString::from(info.gpus));
         appdata.set_os(SharedString::from(info.os));
@@ -40,18 +125,42 @@ fn main() {
     appdata.set_stable_minecraft_versions(string_vec_to_rc(&mc_vers));
     appdata.set_selected_minecraft_version(SharedString::from(mc_vers[0].clone()));
 
-    // Populate Fabric versions
-    let fabric_vers = get_fabric_loader_versions();
-    appdata.set_stable_fabric_loader_versions(string_vec_to_rc(&fabric_vers));
-    appdata.set_selected_fabric_loader_version(SharedString::from(fabric_vers[0].clone()));
-    
+    // Populate platforms
+    let platforms: Vec<String> = Platform::ALL.iter().map(|p| p.name().to_string()).collect();
+    appdata.set_platforms(string_vec_to_rc(&platforms));
+    appdata.set_selected_platform(SharedString::from(Platform::Fabric.name()));
+
     // Populate Mods
-    appdata.set_recommended_mod_list(string_arr_to_rc(&MODS));
-    appdata.set_recommended_mods_toggle(bool_arr_to_rc(&[true; MODS.len()]));
+    let mod_names = ModCatalog::load().optional_names();
+    appdata.set_recommended_mod_list(string_vec_to_rc(&mod_names));
+    appdata.set_recommended_mods_toggle(bool_arr_to_rc(&vec![true; mod_names.len()]));
 
     // Populate JVM
     appdata.set_selected_jvm(SharedString::from("Azul"));
 
+    // Populate presets
+    appdata.set_preset_names(string_vec_to_rc(&preset_names()));
+    set_modpacks(&appdata);
+    set_run_groups(&appdata);
+
+    // Memory
+    appdata.set_ram_alloc(recommended_ram_mb(&mc_vers[0]) as i32);
+
+    // Restore the last configuration
+    settings.apply_to(&appdata);
+    appdata.set_ram_warnings(string_vec_to_rc(&ram_warnings(appdata.get_ram_alloc() as u32)));
+
+    // Populate loader versions for the restored platform
+    let instance = ServerInstance::from_info(&appdata);
+    appdata.set_mods_supported(instance.platform.supports_mods());
+    let loader_vers = get_loader_versions(instance.platform, &instance.mc_ver).unwrap_or_default();
+    let loader_ver = settings.loader_version
+        .filter(|ver| loader_vers.contains(ver))
+        .or_else(|| loader_vers.first().cloned())
+        .unwrap_or_default();
+    appdata.set_stable_loader_versions(string_vec_to_rc(&loader_vers));
+    appdata.set_selected_loader_version(SharedString::from(loader_ver));
+
     // Callbacks
     let callbacks = app.global::<Callbacks>();
     let master_weak_app = app.as_weak();
@@ -65,10 +174,9 @@ fn main() {
             let weak_app_ui = weak_app_root.clone();
             let app = weak_app_ui.upgrade().unwrap();
 
-            let (mc_ver, fabric_ver, jvm, ram) = {
+            let (instance, jvm, ram) = {
                 (
-                    app.global::<Info>().get_selected_minecraft_version(),
-                    app.global::<Info>().get_selected_fabric_loader_version(),
+                    ServerInstance::from_info(&app.global::<Info>()),
                     app.global::<Info>().get_selected_jvm(),
                     app.global::<Info>().get_ram_alloc() as u32,
                 )
@@ -77,11 +185,12 @@ fn main() {
             app.global::<Info>().set_status(BenchmarkingStatus::Install);
 
             // Worker thread
-            let mc_ver_clone = mc_ver.clone();
+            let instance_clone = instance.clone();
             thread::spawn({
                 let tx = tx.clone();
                 move || {
-                    if install_fabric_server(&mc_ver, &fabric_ver, &jvm, ram, &tx).is_err() {
+                    if let Err(e) = install_server(&instance, &jvm, ram, &tx) {
+                        tx.send(InstallerMsg::Error(e.to_string())).ok();
                         return;
                     }
 
@@ -109,25 +218,28 @@ fn main() {
                                 InstallerMsg::Status(s) => {
                                     if s == BenchmarkingStatus::InstallMod {
                                         app.global::<Info>().set_progress(0.0);
-                                        // Check installed mods
-                                        let installed_mods = get_mods(mc_ver_clone.to_string());
+                                        // Check installed mods, including any a crashed run left disabled
+                                        if let Err(e) = restore_mods(&instance_clone) {
+                                            error!("Failed to restore mods: {}", e);
+                                        }
+                                        let installed_mods = get_mods(&instance_clone);
+                                        let catalog = ModCatalog::load();
+                                        let optional_mods = catalog.optional();
                                         let mut skip_mod_installs: bool = true;
-                                        let mut new_recommended_mods_toggle: [bool; MODS.len()] = [false; MODS.len()];
                                         for (i, should_install) in app.global::<Info>().get_recommended_mods_toggle().iter().enumerate() {
-                                            if !should_install {
+                                            if !should_install || !instance_clone.platform.supports_mods() {
                                                 continue;
                                             }
 
-                                            let mod_to_check = MODS.get(i).unwrap();
-                                            if !installed_mods.contains(&mod_to_check.to_lowercase()) {
+                                            let mod_to_check = optional_mods.get(i).unwrap();
+                                            if !installed_mods.contains(&mod_to_check.mod_id) {
                                                 skip_mod_installs = false;
-                                                new_recommended_mods_toggle[i] = true;
                                             }
                                         }
 
                                         // Check required mods
-                                        for req_mod in REQ_MODS {
-                                            if !installed_mods.contains(&req_mod.to_lowercase()) {
+                                        for req_mod in catalog.required(instance_clone.platform) {
+                                            if !installed_mods.contains(&req_mod.mod_id) {
                                                 skip_mod_installs = false;
                                                 break;
                                             }
@@ -136,10 +248,9 @@ fn main() {
                                         // Set first mod to install
                                         if skip_mod_installs {
                                             app.global::<Info>().set_status(BenchmarkingStatus::Running);
-                                            println!("Begin Benchmark");
+                                            info!("Starting benchmark");
                                             start_benchmark(&weak_app_ui);
                                         } else {
-                                            app.global::<Info>().set_recommended_mods_toggle(bool_arr_to_rc(&new_recommended_mods_toggle));
                                             app.global::<Callbacks>().invoke_next_mod();
 
                                             app.global::<Info>().set_status(s);
@@ -152,7 +263,12 @@ fn main() {
                                     app.global::<Info>().set_status(s);
                                 }
                                 InstallerMsg::Error(e) => {
-                                    eprintln!("Installer error: {}", e);
+                                    error!("Installer error: {}", e);
+                                    // Back to the Configure panel, where Verify / Repair can pick it up
+                                    app.global::<Info>().set_instance_status(SharedString::from(format!("Install failed: {}", e)));
+                                    app.global::<Info>().set_status(BenchmarkingStatus::Configure);
+                                    timer_for_cb.borrow().stop();
+                                    return;
                                 }
                                 InstallerMsg::InstallingMsg(s) => {
                                     app.global::<Info>().set_installer_msg(SharedString::from(s));
@@ -170,17 +286,22 @@ fn main() {
     callbacks.on_install_mod({
         let weak_app_root = master_weak_app.clone().unwrap();
         move |string| {
-            let mc_ver = weak_app_root.global::<Info>().get_selected_minecraft_version();
-            let url = get_url(string.to_string(), mc_ver.to_string());
-            webbrowser::open(&url).unwrap();
+            let instance = ServerInstance::from_info(&weak_app_root.global::<Info>());
+            match get_url(string.to_string(), instance.mc_ver.clone(), instance.platform) {
+                Some(url) => webbrowser::open(&url).unwrap(),
+                None => {
+                    if let Err(e) = install_local_mod(&string, &instance) {
+                        error!("Failed to copy {}: {}", string, e);
+                    }
+                }
+            }
         }
     });
     
     callbacks.on_open_mods_folder({
         let weak_app_root = master_weak_app.clone().unwrap();
         move || {
-            let mc_ver = weak_app_root.global::<Info>().get_selected_minecraft_version();
-            let directory = server_dir().join(mc_ver.to_string()).join("mods");
+            let directory = ServerInstance::from_info(&weak_app_root.global::<Info>()).mods_dir();
             open::that(directory).unwrap();
         }
     });
@@ -190,21 +311,25 @@ fn main() {
         move || {
             let app = weak_app_root.upgrade().unwrap();
             let index = app.global::<Info>().get_current_mod_download_index();
-            let mc_ver = app.global::<Info>().get_selected_minecraft_version();
+            let instance = ServerInstance::from_info(&app.global::<Info>());
+            let catalog = ModCatalog::load();
+            let optional_mods = catalog.optional();
+            let req_mods = catalog.required(instance.platform);
             let mut next_index = index + 1;
 
             loop {
-                if next_index >= MODS.len() as i32 {
-                    let req_mods_index = next_index - MODS.len() as i32;
-                    if req_mods_index < REQ_MODS.len() as i32 {
+                if next_index >= optional_mods.len() as i32 {
+                    let req_mods_index = next_index - optional_mods.len() as i32;
+                    if req_mods_index < req_mods.len() as i32 {
                         // Look at required mods
-                        let next_mod = REQ_MODS.get(req_mods_index as usize).unwrap();
-                        if is_mod_installed(next_mod.to_string(), mc_ver.to_string()) {
+                        let next_mod = req_mods.get(req_mods_index as usize).unwrap();
+                        if is_mod_installed(next_mod.name.clone(), &instance)
+                            || install_local_mod(&next_mod.name, &instance).unwrap_or(false) {
                             next_index += 1;
                             app.global::<Info>().set_current_mod_download_index(next_index);
                             continue;
                         } else {
-                            app.global::<Info>().set_current_mod_download(SharedString::from(*next_mod));
+                            app.global::<Info>().set_current_mod_download(SharedString::from(&next_mod.name));
                             next_index += 1;
                             app.global::<Info>().set_current_mod_download_index(next_index);
                             return;
@@ -213,23 +338,516 @@ fn main() {
 
                     app.global::<Info>().set_current_mod_download_index(-1);
                     app.global::<Info>().set_status(BenchmarkingStatus::Running);
-                    println!("Begin Benchmark");
+                    info!("Starting benchmark");
                     start_benchmark(&weak_app_root);
                     return;
                 }
 
-                if app.global::<Info>().get_recommended_mods_toggle().iter().nth(next_index as usize).unwrap() {
-                    break;
+                if instance.platform.supports_mods() && app.global::<Info>().get_recommended_mods_toggle().iter().nth(next_index as usize).unwrap() {
+                    // Selected mods that are installed, or local jars that can just be copied, need no prompt
+                    let next_mod = optional_mods.get(next_index as usize).unwrap();
+                    if !is_mod_installed(next_mod.name.clone(), &instance)
+                        && !install_local_mod(&next_mod.name, &instance).unwrap_or(false) {
+                        break;
+                    }
                 }
 
                 next_index += 1;
             }
 
             app.global::<Info>().set_current_mod_download_index(next_index);
-            let next_mod = MODS.get(next_index as usize).unwrap();
-            app.global::<Info>().set_current_mod_download(SharedString::from(*next_mod));
+            let next_mod = optional_mods.get(next_index as usize).unwrap();
+            app.global::<Info>().set_current_mod_download(SharedString::from(&next_mod.name));
+        }
+    });
+
+    callbacks.on_open_eula(|| {
+        webbrowser::open(EULA_URL).unwrap();
+    });
+
+    callbacks.on_eula_answered({
+        let weak_app_root = master_weak_app.clone();
+        move |accepted| {
+            let app = weak_app_root.upgrade().unwrap();
+            let mut settings = Settings::load();
+            settings.eula_accepted = accepted;
+            settings.save().unwrap();
+
+            app.global::<Info>().set_eula_accepted(accepted);
+            if !accepted {
+                app.global::<Info>().set_instance_status(SharedString::from("Servers can't be installed without accepting the Minecraft EULA"));
+            }
+        }
+    });
+
+    callbacks.on_save_preset({
+        let weak_app_root = master_weak_app.clone();
+        move |name| {
+            let app = weak_app_root.upgrade().unwrap();
+            let appdata = app.global::<Info>();
+            let preset = Preset::from_info(&name, &appdata);
+            match preset.save() {
+                Ok(_) => {
+                    appdata.set_preset_names(string_vec_to_rc(&preset_names()));
+                    appdata.set_selected_preset(SharedString::from(&preset.name));
+                    appdata.set_instance_status(SharedString::from(format!("Saved preset {}", preset.name)));
+                }
+                Err(e) => appdata.set_instance_status(SharedString::from(format!("Failed to save preset: {}", e))),
+            }
+        }
+    });
+
+    callbacks.on_load_preset({
+        let weak_app_root = master_weak_app.clone();
+        move |name| {
+            let app = weak_app_root.upgrade().unwrap();
+            let appdata = app.global::<Info>();
+            match Preset::load(&name) {
+                Ok(preset) => {
+                    let instance = match preset.instance() {
+                        Ok(instance) => instance,
+                        Err(e) => {
+                            appdata.set_instance_status(SharedString::from(format!("Failed to load preset: {}", e)));
+                            return;
+                        }
+                    };
+                    preset.apply_to(&appdata);
+                    appdata.set_preset_name(SharedString::from(&preset.name));
+                    let mismatched = preset.pin_mismatches(&instance);
+                    let status = if mismatched.is_empty() {
+                        format!("Loaded preset {}", preset.name)
+                    } else {
+                        format!("Installed mod versions differ: {}", mismatched.join(", "))
+                    };
+                    appdata.set_instance_status(SharedString::from(status));
+                    // A modpack comes with its own loader version, the list is only fetched for plain instances
+                    if instance.pack.is_empty() {
+                        appdata.set_selected_modpack(SharedString::from(NO_MODPACK));
+                        app.global::<Callbacks>().invoke_refresh_loader_versions();
+                    } else {
+                        select_modpack(&appdata, &instance);
+                    }
+                }
+                Err(e) => appdata.set_instance_status(SharedString::from(format!("Failed to load preset: {}", e))),
+            }
+        }
+    });
+
+    callbacks.on_export_preset({
+        let weak_app_root = master_weak_app.clone();
+        move |name| {
+            let app = weak_app_root.upgrade().unwrap();
+            let export_dir = dirs::download_dir().unwrap_or_else(data_dir);
+            let status = match Preset::load(&name).and_then(|preset| preset.write(&export_dir)) {
+                Ok(path) => format!("Exported to {}", path.display()),
+                Err(e) => format!("Failed to export preset: {}", e),
+            };
+            app.global::<Info>().set_instance_status(SharedString::from(status));
+        }
+    });
+
+    callbacks.on_import_preset({
+        let weak_app_root = master_weak_app.clone();
+        move |path| {
+            let app = weak_app_root.upgrade().unwrap();
+            let appdata = app.global::<Info>();
+            match Preset::import(Path::new(path.trim())) {
+                Ok(preset) => {
+                    appdata.set_preset_names(string_vec_to_rc(&preset_names()));
+                    appdata.set_selected_preset(SharedString::from(&preset.name));
+                    app.global::<Callbacks>().invoke_load_preset(SharedString::from(&preset.name));
+                }
+                Err(e) => appdata.set_instance_status(SharedString::from(format!("Failed to import preset: {}", e))),
+            }
+        }
+    });
+
+    callbacks.on_add_mod({
+        let weak_app_root = master_weak_app.clone();
+        move |source| {
+            let app = weak_app_root.upgrade().unwrap();
+            let appdata = app.global::<Info>();
+            let mut catalog = ModCatalog::load();
+            let status = match catalog.add(&source) {
+                Ok(new_mod) => {
+                    // Keep the user's checks and tick the new mod
+                    let mut checked = toggled_names(&appdata.get_recommended_mod_list(), &appdata.get_recommended_mods_toggle());
+                    checked.push(new_mod.name.clone());
+                    let status = format!("Added {}", new_mod.name);
+                    let mod_names = catalog.optional_names();
+                    appdata.set_recommended_mod_list(string_vec_to_rc(&mod_names));
+                    appdata.set_recommended_mods_toggle(checked_names(&appdata.get_recommended_mod_list(), &checked));
+                    appdata.set_new_mod_source(SharedString::new());
+                    Settings::store(&appdata);
+                    status
+                }
+                Err(e) => format!("Failed to add {}: {}", source, e),
+            };
+            appdata.set_instance_status(SharedString::from(status));
+        }
+    });
+
+    callbacks.on_remove_mod({
+        let weak_app_root = master_weak_app.clone();
+        move |name| {
+            let app = weak_app_root.upgrade().unwrap();
+            let appdata = app.global::<Info>();
+            let mut catalog = ModCatalog::load();
+            if let Err(e) = catalog.remove(&name) {
+                appdata.set_instance_status(SharedString::from(format!("Failed to remove {}: {}", name, e)));
+                return;
+            }
+            let checked = toggled_names(&appdata.get_recommended_mod_list(), &appdata.get_recommended_mods_toggle());
+            appdata.set_recommended_mod_list(string_vec_to_rc(&catalog.optional_names()));
+            appdata.set_recommended_mods_toggle(checked_names(&appdata.get_recommended_mod_list(), &checked));
+            Settings::store(&appdata);
+        }
+    });
+
+    callbacks.on_settings_changed({
+        let weak_app_root = master_weak_app.clone();
+        move || {
+            let app = weak_app_root.upgrade().unwrap();
+            let appdata = app.global::<Info>();
+            Settings::store(&appdata);
+            appdata.set_ram_warnings(string_vec_to_rc(&ram_warnings(appdata.get_ram_alloc() as u32)));
+        }
+    });
+
+    callbacks.on_recommended_ram(|mc_ver| recommended_ram_mb(&mc_ver) as i32);
+
+    callbacks.on_refresh_loader_versions({
+        let weak_app_root = master_weak_app.clone();
+        move || {
+            let app = weak_app_root.upgrade().unwrap();
+            let instance = ServerInstance::from_info(&app.global::<Info>());
+            app.global::<Info>().set_mods_supported(instance.platform.supports_mods());
+
+            // Paper-like platforms list builds per Minecraft version, so fetch off the UI thread
+            let weak_app = weak_app_root.clone();
+            thread::spawn(move || {
+                let loader_vers = get_loader_versions(instance.platform, &instance.mc_ver).unwrap_or_else(|e| {
+                    warn!("Failed to fetch {} versions: {}", instance.platform.name(), e);
+                    Vec::new()
+                });
+                weak_app.upgrade_in_event_loop(move |app| {
+                    let appdata = app.global::<Info>();
+                    // Keep the selected version when the new list still offers it, e.g. after loading a preset
+                    let selected = appdata.get_selected_loader_version().to_string();
+                    let loader_ver = if loader_vers.contains(&selected) {
+                        selected
+                    } else {
+                        loader_vers.first().cloned().unwrap_or_default()
+                    };
+                    appdata.set_stable_loader_versions(string_vec_to_rc(&loader_vers));
+                    appdata.set_selected_loader_version(SharedString::from(loader_ver));
+                    Settings::store(&appdata);
+                }).ok();
+            });
+        }
+    });
+
+    callbacks.on_repair_instance({
+        let weak_app_root = master_weak_app.clone();
+        move || {
+            let (tx, rx) = mpsc::channel::<InstallerMsg>();
+            let app = weak_app_root.upgrade().unwrap();
+
+            let instance = ServerInstance::from_info(&app.global::<Info>());
+            let jvm = app.global::<Info>().get_selected_jvm();
+            let ram = app.global::<Info>().get_ram_alloc() as u32;
+
+            app.global::<Info>().set_progress(0.0);
+            app.global::<Info>().set_status(BenchmarkingStatus::Install);
+
+            // Worker thread
+            thread::spawn(move || {
+                let health = InstanceManifest::load(&instance).verify(&instance);
+                let result = if health.is_ok() {
+                    format!("{} is intact", instance.name())
+                } else {
+                    match install_server(&instance, &jvm, ram, &tx) {
+                        Ok(()) => format!("Repaired {}", health.missing().join(", ")),
+                        Err(e) => format!("Repair failed: {}", e),
+                    }
+                };
+                tx.send(InstallerMsg::InstallingMsg(result)).ok();
+                tx.send(InstallerMsg::Status(BenchmarkingStatus::Configure)).ok();
+            });
+
+            poll_installer(&weak_app_root, rx);
+        }
+    });
+
+    callbacks.on_import_modpack({
+        let weak_app_root = master_weak_app.clone();
+        move |path| {
+            let (tx, rx) = mpsc::channel::<InstallerMsg>();
+            let app = weak_app_root.upgrade().unwrap();
+
+            let jvm = app.global::<Info>().get_selected_jvm();
+            let ram = app.global::<Info>().get_ram_alloc() as u32;
+
+            app.global::<Info>().set_progress(0.0);
+            app.global::<Info>().set_status(BenchmarkingStatus::Install);
+
+            // Worker thread
+            let weak_app = weak_app_root.clone();
+            thread::spawn(move || {
+                let result = match import_mrpack(Path::new(path.trim()), &jvm, ram, &tx) {
+                    Ok(instance) => {
+                        let result = format!("Imported {}", instance.name());
+                        weak_app.upgrade_in_event_loop(move |app| {
+                            let appdata = app.global::<Info>();
+                            set_modpacks(&appdata);
+                            select_modpack(&appdata, &instance);
+                            appdata.set_mrpack_path(SharedString::new());
+                        }).ok();
+                        result
+                    }
+                    Err(e) => format!("Modpack import failed: {}", e),
+                };
+                tx.send(InstallerMsg::InstallingMsg(result)).ok();
+                tx.send(InstallerMsg::Status(BenchmarkingStatus::Configure)).ok();
+            });
+
+            poll_installer(&weak_app_root, rx);
+        }
+    });
+
+    callbacks.on_compare_runs({
+        let weak_app_root = master_weak_app.clone();
+        move |a, b| {
+            let app = weak_app_root.upgrade().unwrap();
+            let appdata = app.global::<Info>();
+            match compare::compare(entry_key(&a), entry_key(&b)) {
+                Ok(lines) => {
+                    appdata.set_result_title(SharedString::from("Comparison"));
+                    appdata.set_result_lines(string_vec_to_rc(&lines));
+                    appdata.set_result_keys(string_vec_to_rc(&[entry_key(&a).to_string(), entry_key(&b).to_string()]));
+                    appdata.set_status(BenchmarkingStatus::Finished);
+                }
+                Err(e) => appdata.set_instance_status(SharedString::from(format!("Compare failed: {}", e))),
+            }
+        }
+    });
+
+    callbacks.on_export_results({
+        let weak_app_root = master_weak_app.clone();
+        move |format| {
+            let app = weak_app_root.upgrade().unwrap();
+            let appdata = app.global::<Info>();
+            let format = ExportFormat::from_name(&format).unwrap();
+            let keys: Vec<String> = appdata.get_result_keys().iter().map(|key| key.to_string()).collect();
+            let runs = if keys.is_empty() { runs_since(None) } else { runs_matching(&keys) };
+            let dir = dirs::download_dir().unwrap_or_else(data_dir);
+            let status = match export_to(&runs, format, &dir) {
+                Ok(path) => format!("Exported {} runs to {}", runs.len(), path.display()),
+                Err(e) => format!("Export failed: {}", e),
+            };
+            appdata.set_export_status(SharedString::from(status));
+        }
+    });
+
+    callbacks.on_export_bundles({
+        let weak_app_root = master_weak_app.clone();
+        move || {
+            let app = weak_app_root.upgrade().unwrap();
+            let appdata = app.global::<Info>();
+            let keys: Vec<String> = appdata.get_result_keys().iter().map(|key| key.to_string()).collect();
+            let dir = dirs::download_dir().unwrap_or_else(data_dir);
+            let runs = runs_matching(&keys);
+            let status = match runs.iter().map(|run| export_bundle(run, &dir)).collect::<std::io::Result<Vec<_>>>() {
+                Ok(paths) => format!("Wrote {} bundles to {}", paths.len(), dir.display()),
+                Err(e) => format!("Bundle export failed: {}", e),
+            };
+            appdata.set_export_status(SharedString::from(status));
+        }
+    });
+
+    callbacks.on_filter_log({
+        let weak_app_root = master_weak_app.clone();
+        move || {
+            let app = weak_app_root.upgrade().unwrap();
+            filter_log(&app.global::<Info>());
+        }
+    });
+
+    callbacks.on_save_log({
+        let weak_app_root = master_weak_app.clone();
+        move || {
+            let app = weak_app_root.upgrade().unwrap();
+            let appdata = app.global::<Info>();
+            let dir = dirs::download_dir().unwrap_or_else(data_dir);
+            let status = match save_log(&appdata, &dir) {
+                Ok(path) => format!("Saved to {}", path.display()),
+                Err(e) => format!("Failed to save log: {}", e),
+            };
+            appdata.set_log_status(SharedString::from(status));
+        }
+    });
+
+    callbacks.on_import_bundle({
+        let weak_app_root = master_weak_app.clone();
+        move |path| {
+            let app = weak_app_root.upgrade().unwrap();
+            let appdata = app.global::<Info>();
+            let status = match import_bundle(Path::new(path.trim())) {
+                Ok(run) => {
+                    appdata.set_bundle_path(SharedString::new());
+                    set_run_groups(&appdata);
+                    format!("Imported run {} ({:.1} CPS)", run.id, run.average_cps)
+                }
+                Err(e) => format!("Bundle import failed: {}", e),
+            };
+            appdata.set_instance_status(SharedString::from(status));
+        }
+    });
+
+    callbacks.on_submit_results({
+        let weak_app_root = master_weak_app.clone();
+        move || {
+            let app = weak_app_root.upgrade().unwrap();
+            let keys: Vec<String> = app.global::<Info>().get_result_keys().iter().map(|key| key.to_string()).collect();
+            let consent = app.global::<Info>().get_leaderboard_consent();
+            app.global::<Info>().set_export_status(SharedString::from("Submitting..."));
+
+            let weak_app = weak_app_root.clone();
+            thread::spawn(move || {
+                let submitted = HttpLeaderboard::configured().and_then(|backend| submit_runs(&backend, &runs_matching(&keys), consent));
+                let status = match submitted {
+                    Ok(count) => format!("Submitted {} runs to the leaderboard", count),
+                    Err(e) => format!("Leaderboard submission failed: {}", e),
+                };
+                weak_app.upgrade_in_event_loop(move |app| {
+                    app.global::<Info>().set_export_status(SharedString::from(status));
+                }).ok();
+            });
+        }
+    });
+
+    callbacks.on_fetch_leaderboard({
+        let weak_app_root = master_weak_app.clone();
+        move |cpu, minecraft_version, config| {
+            let app = weak_app_root.upgrade().unwrap();
+            app.global::<Info>().set_instance_status(SharedString::from("Fetching leaderboard..."));
+            let filter = LeaderboardFilter {
+                cpu: cpu.trim().to_string(),
+                minecraft_version: minecraft_version.trim().to_string(),
+                config: config.trim().to_string(),
+            };
+
+            let weak_app = weak_app_root.clone();
+            thread::spawn(move || {
+                let result = HttpLeaderboard::configured().and_then(|backend| backend.top_runs(&filter, LEADERBOARD_SIZE));
+                weak_app.upgrade_in_event_loop(move |app| {
+                    let appdata = app.global::<Info>();
+                    match result {
+                        Ok(entries) => {
+                            appdata.set_instance_status(SharedString::new());
+                            appdata.set_result_title(SharedString::from("Leaderboard"));
+                            appdata.set_result_lines(string_vec_to_rc(&leaderboard_report(&entries)));
+                            appdata.set_result_keys(Default::default());
+                            appdata.set_status(BenchmarkingStatus::Finished);
+                        }
+                        Err(e) => appdata.set_instance_status(SharedString::from(format!("Failed to fetch leaderboard: {}", e))),
+                    }
+                }).ok();
+            });
+        }
+    });
+
+    callbacks.on_modpack_selected({
+        let weak_app_root = master_weak_app.clone();
+        move |pack| {
+            let app = weak_app_root.upgrade().unwrap();
+            if let Some(instance) = imported_packs().into_iter().find(|instance| instance.pack == pack.as_str()) {
+                select_modpack(&app.global::<Info>(), &instance);
+            }
         }
     });
 
     app.run().unwrap();
 }
+
+/// Lists the imported modpacks, keeping the selection if it still exists
+fn set_modpacks(appdata: &Info) {
+    let mut packs = vec![NO_MODPACK.to_string()];
+    packs.extend(imported_packs().into_iter().map(|instance| instance.pack));
+    if !packs.contains(&appdata.get_selected_modpack().to_string()) {
+        appdata.set_selected_modpack(SharedString::from(NO_MODPACK));
+    }
+    appdata.set_modpacks(string_vec_to_rc(&packs));
+}
+
+/// Points the Configure panel at a modpack instance and the versions it was built for
+fn select_modpack(appdata: &Info, instance: &ServerInstance) {
+    appdata.set_selected_modpack(SharedString::from(&instance.pack));
+    appdata.set_selected_minecraft_version(SharedString::from(&instance.mc_ver));
+    appdata.set_selected_platform(SharedString::from(instance.platform.name()));
+    appdata.set_selected_loader_version(SharedString::from(&instance.loader_ver));
+    appdata.set_mods_supported(instance.platform.supports_mods());
+    appdata.set_stable_loader_versions(string_vec_to_rc(std::slice::from_ref(&instance.loader_ver)));
+}
+
+/// Shows installer progress and messages until the worker sends the status to return to
+fn poll_installer(app: &slint::Weak<App>, rx: mpsc::Receiver<InstallerMsg>) {
+    let weak_app_timer = app.clone();
+    let timer = Rc::new(RefCell::new(slint::Timer::default()));
+    let timer_for_cb = timer.clone();
+
+    timer.borrow().start(
+        slint::TimerMode::Repeated,
+        std::time::Duration::from_millis(50),
+        move || {
+            if let Some(app) = weak_app_timer.upgrade() {
+                while let Ok(msg) = rx.try_recv() {
+                    match msg {
+                        InstallerMsg::Progress(p) => {
+                            app.global::<Info>().set_progress(p);
+                        }
+                        InstallerMsg::Status(s) => {
+                            app.global::<Info>().set_status(s);
+                            timer_for_cb.borrow().stop();
+                            return;
+                        }
+                        InstallerMsg::Error(e) => {
+                            error!("Installer error: {}", e);
+                        }
+                        InstallerMsg::InstallingMsg(s) => {
+                            app.global::<Info>().set_installer_msg(SharedString::from(s.clone()));
+                            app.global::<Info>().set_instance_status(SharedString::from(s));
+                        }
+                    }
+                }
+            } else {
+                timer_for_cb.borrow().stop();
+            }
+        },
+    );
+}
+
+/// `export`: writes the runs selected by `--since` in `--format` to `--output` or the data directory
+fn export_cli(cli: &CliArgs) -> std::io::Result<()> {
+    let name = cli.format.as_deref().unwrap_or("md");
+    let format = ExportFormat::from_name(name).ok_or_else(|| {
+        std::io::Error::new(std::io::ErrorKind::InvalidInput, format!("Unknown format {}, expected csv, json, md or html", name))
+    })?;
+    let since = match &cli.since {
+        Some(since) => Some(parse_since(since).ok_or_else(|| {
+            std::io::Error::new(std::io::ErrorKind::InvalidInput, format!("Invalid date {}, expected YYYY-MM-DD", since))
+        })?),
+        None => None,
+    };
+
+    let runs = runs_since(since);
+    let path = match &cli.output {
+        Some(path) => {
+            write_export(&runs, format, path)?;
+            path.clone()
+        }
+        None => export_to(&runs, format, &data_dir())?,
+    };
+    println!("Exported {} runs to {}", runs.len(), path.display());
+    Ok(())
+}
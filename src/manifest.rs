@@ -0,0 +1,106 @@
+use std::fs::File;
+use std::path::Path;
+use std::{fs, io};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256, Sha512};
+use crate::io::{eula_accepted, ServerInstance};
+
+pub const MANIFEST_FILE: &str = "subchunker-instance.json";
+
+/// Written into the instance folder as each install step completes
+#[derive(Serialize, Deserialize, Default)]
+pub struct InstanceManifest {
+    pub platform: String,
+    pub mc_ver: String,
+    pub loader_ver: String,
+    pub installer_ver: String,
+    pub jar_sha256: String,
+    pub libraries_ready: bool,
+    pub eula_accepted: bool,
+    /// Slug of the modpack the instance was imported from, empty otherwise
+    #[serde(default)]
+    pub pack: String,
+    /// Modpack name and version for display
+    #[serde(default)]
+    pub pack_name: String,
+    /// Mod IDs the modpack installed, kept active whatever mods are selected
+    #[serde(default)]
+    pub pack_mods: Vec<String>,
+}
+
+/// Which install steps are intact on disk
+pub struct InstanceHealth {
+    pub jar: bool,
+    pub libraries: bool,
+    pub eula: bool,
+}
+
+impl InstanceHealth {
+    pub fn is_ok(&self) -> bool {
+        self.jar && self.libraries && self.eula
+    }
+
+    /// Human readable list of the steps that need repairing
+    pub fn missing(&self) -> Vec<&'static str> {
+        let mut missing = Vec::new();
+        if !self.jar {
+            missing.push("server jar");
+        }
+        if !self.libraries {
+            missing.push("libraries");
+        }
+        if !self.eula {
+            missing.push("EULA");
+        }
+        missing
+    }
+}
+
+impl InstanceManifest {
+    /// Loads the manifest, or an empty one for new, legacy or corrupt instances
+    pub fn load(instance: &ServerInstance) -> Self {
+        Self::read(&instance.dir()).unwrap_or_else(|_| InstanceManifest {
+            platform: instance.platform.name().to_string(),
+            mc_ver: instance.mc_ver.clone(),
+            loader_ver: instance.loader_ver.clone(),
+            installer_ver: instance.installer_ver.clone(),
+            pack: instance.pack.clone(),
+            ..Default::default()
+        })
+    }
+
+    /// Reads the manifest in an instance folder
+    pub fn read(dir: &Path) -> io::Result<Self> {
+        let json = fs::read_to_string(dir.join(MANIFEST_FILE))?;
+        serde_json::from_str(&json).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    pub fn save(&self, instance: &ServerInstance) -> io::Result<()> {
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        fs::write(instance.dir().join(MANIFEST_FILE), json)
+    }
+
+    pub fn verify(&self, instance: &ServerInstance) -> InstanceHealth {
+        let jar = !self.jar_sha256.is_empty()
+            && sha256_file(&instance.launch_file()).is_ok_and(|hash| hash == self.jar_sha256);
+        let libraries = jar && self.libraries_ready && instance.dir().join("libraries").is_dir();
+        let eula = self.eula_accepted && eula_accepted(instance);
+
+        InstanceHealth { jar, libraries, eula }
+    }
+}
+
+pub fn sha256_file(path: &Path) -> io::Result<String> {
+    let mut file = File::open(path)?;
+    let mut hasher = Sha256::new();
+    io::copy(&mut file, &mut hasher)?;
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+pub fn sha512_file(path: &Path) -> io::Result<String> {
+    let mut file = File::open(path)?;
+    let mut hasher = Sha512::new();
+    io::copy(&mut file, &mut hasher)?;
+    Ok(format!("{:x}", hasher.finalize()))
+}
@@ -0,0 +1,50 @@
+use hwinfo_rs::hwinfo;
+use log::warn;
+use crate::versions::satisfies_any;
+
+pub const MIN_RAM_MB: u32 = 512;
+/// Steps of the Memory slider
+pub const RAM_STEP_MB: u32 = 256;
+/// HotSpot stops compressing object pointers once the heap reaches 32 GB, so anything
+/// just above holds fewer objects than just below
+pub const COMPRESSED_OOPS_LIMIT_MB: u32 = 32 * 1024;
+
+/// Physical memory not in use by other programs
+pub fn available_mb() -> Option<u64> {
+    hwinfo::memory_info().ok().map(|info| info.available_bytes as u64 / 1_048_576)
+}
+
+/// Heap for a Minecraft version, capped at three quarters of the free memory.
+/// 1.18 doubled the world height, and generating it takes more memory.
+pub fn recommended_ram_mb(mc_ver: &str) -> u32 {
+    let wanted = if satisfies_any(mc_ver, &[">=1.18".to_string()]).unwrap_or(true) { 6144 } else { 4096 };
+    let available = available_mb();
+    let cap = available.map_or(wanted, |available| (available * 3 / 4) as u32);
+    if let Some(available) = available
+        && cap < MIN_RAM_MB * 2
+    {
+        warn!("Only {} MB of RAM is free, the server will likely run out of memory or swap", available);
+    }
+    // The slider can't go below MIN_RAM_MB, anything else stays under the cap
+    (wanted.min(cap) / RAM_STEP_MB * RAM_STEP_MB).max(MIN_RAM_MB)
+}
+
+/// Problems with a heap of `memory_mb` on this machine
+pub fn ram_warnings(memory_mb: u32) -> Vec<String> {
+    let mut warnings = Vec::new();
+    if let Some(available) = available_mb()
+        && memory_mb as u64 > available
+    {
+        warnings.push(format!(
+            "Only {} MB of RAM is free for a {} MB heap, part of it would be swapped out",
+            available, memory_mb
+        ));
+    }
+    if memory_mb >= COMPRESSED_OOPS_LIMIT_MB {
+        warnings.push(format!(
+            "Heaps of 32 GB and more lose compressed object pointers, {} MB or less is usually faster",
+            COMPRESSED_OOPS_LIMIT_MB - 1024
+        ));
+    }
+    warnings
+}
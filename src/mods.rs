@@ -1,77 +1,780 @@
 use std::{fs, io};
+use std::collections::BTreeMap;
 use std::fs::File;
-use std::io::Read;
-use std::path::Path;
-use serde::Deserialize;
+use std::io::{Cursor, Read, Seek};
+use std::path::{Path, PathBuf};
+use reqwest::blocking::Client;
+use serde::{Deserialize, Serialize};
+use log::warn;
 use zip::ZipArchive;
-use crate::io::server_dir;
+use crate::io::{mod_catalog_file, Platform, ServerInstance};
+use crate::manifest::{sha256_file, InstanceManifest};
+use crate::versions::satisfies_any;
 
 // Mods
-pub const MODS: [&str; 8] = ["Lithium", "Fabric-Api", "Ferritecore", "C2me", "Servercore",
-                                 "Structure_Layout_Optimizer", "ResourcefulConfig", "ScalableLux"];
-pub const MOD_URLS: [&str; 10] = ["https://modrinth.com/mod/lithium/versions", "https://modrinth.com/mod/fabric-api/versions",
-                                     "https://modrinth.com/mod/ferrite-core/versions", "https://modrinth.com/mod/c2me-fabric/versions",
-                                     "https://modrinth.com/mod/servercore/versions", "https://modrinth.com/mod/structure-layout-optimizer/versions",
-                                     "https://modrinth.com/mod/resourceful-config/versions", "https://modrinth.com/mod/scalablelux/versions",
-                                     "https://modrinth.com/plugin/chunky/versions", "https://modrinth.com/project/subchunker-companion/versions"];
+pub const COMPANION_ID: &str = "subchunker_companion";
 
-pub const REQ_MODS: [&str; 2] = ["Chunky", "SubChunker_Companion"];
+/// Where a catalog mod comes from
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ModSource {
+    /// Modrinth project ID or slug
+    Modrinth { project: String },
+    /// A jar on disk, copied into the mods folder when installing
+    Local { path: PathBuf },
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct CatalogMod {
+    pub name: String,
+    /// ID from the jar's metadata, used to detect whether the mod is installed
+    pub mod_id: String,
+    pub source: ModSource,
+    /// Required mods are always installed and never shown as a checkbox
+    #[serde(default)]
+    pub required: bool,
+}
+
+impl CatalogMod {
+    fn modrinth(name: &str, mod_id: &str, project: &str, required: bool) -> Self {
+        CatalogMod {
+            name: name.to_string(),
+            mod_id: mod_id.to_string(),
+            source: ModSource::Modrinth { project: project.to_string() },
+            required,
+        }
+    }
+}
+
+/// Every mod SubChunker knows about, stored in `data/mods.json`
+#[derive(Serialize, Deserialize)]
+pub struct ModCatalog {
+    pub mods: Vec<CatalogMod>,
+}
+
+impl Default for ModCatalog {
+    fn default() -> Self {
+        ModCatalog {
+            mods: vec![
+                CatalogMod::modrinth("Lithium", "lithium", "lithium", false),
+                CatalogMod::modrinth("Fabric-Api", "fabric-api", "fabric-api", false),
+                CatalogMod::modrinth("Ferritecore", "ferritecore", "ferrite-core", false),
+                CatalogMod::modrinth("C2me", "c2me", "c2me-fabric", false),
+                CatalogMod::modrinth("Servercore", "servercore", "servercore", false),
+                CatalogMod::modrinth("Structure_Layout_Optimizer", "structure_layout_optimizer", "structure-layout-optimizer", false),
+                CatalogMod::modrinth("ResourcefulConfig", "resourcefulconfig", "resourceful-config", false),
+                CatalogMod::modrinth("ScalableLux", "scalablelux", "scalablelux", false),
+                CatalogMod::modrinth("Chunky", "chunky", "chunky", true),
+                CatalogMod::modrinth("SubChunker_Companion", COMPANION_ID, "subchunker-companion", true),
+            ],
+        }
+    }
+}
+
+impl ModCatalog {
+    pub fn load() -> Self {
+        fs::read_to_string(mod_catalog_file())
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        fs::write(mod_catalog_file(), json)
+    }
+
+    /// Mods the user can toggle in the Configure panel
+    pub fn optional(&self) -> Vec<&CatalogMod> {
+        self.mods.iter().filter(|m| !m.required).collect()
+    }
+
+    pub fn optional_names(&self) -> Vec<String> {
+        self.optional().iter().map(|m| m.name.clone()).collect()
+    }
+
+    /// Required mods or plugins for a platform; the companion only exists where `has_companion` holds
+    pub fn required(&self, platform: Platform) -> Vec<&CatalogMod> {
+        self.mods
+            .iter()
+            .filter(|m| m.required && (m.mod_id != COMPANION_ID || platform.has_companion()))
+            .collect()
+    }
+
+    pub fn get(&self, name: &str) -> Option<&CatalogMod> {
+        self.mods.iter().find(|m| m.name == name)
+    }
+
+    /// The jar ID for a catalog name, falling back to the lowercase name
+    pub fn mod_id(&self, name: &str) -> String {
+        self.get(name).map(|m| m.mod_id.clone()).unwrap_or_else(|| name.to_lowercase())
+    }
+
+    /// Adds a Modrinth project ID or slug, or the path of a local jar
+    pub fn add(&mut self, source: &str) -> io::Result<&CatalogMod> {
+        let source = source.trim();
+        let path = Path::new(source);
+        let new_mod = if source.ends_with(".jar") && path.is_file() {
+            let installed = read_mod(path)?;
+            CatalogMod {
+                name: installed.id.clone(),
+                mod_id: installed.id,
+                source: ModSource::Local { path: path.canonicalize()? },
+                required: false,
+            }
+        } else {
+            modrinth_project(source)?
+        };
+
+        if self.mods.iter().any(|m| m.name == new_mod.name || m.mod_id == new_mod.mod_id) {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("{} is already in the mod list", new_mod.name),
+            ));
+        }
+        self.mods.push(new_mod);
+        self.save()?;
+        Ok(self.mods.last().unwrap())
+    }
+
+    pub fn remove(&mut self, name: &str) -> io::Result<()> {
+        self.mods.retain(|m| m.name != name || m.required);
+        self.save()
+    }
+}
+
+/// Looks a project up on Modrinth and reads the mod ID from its newest jar
+fn modrinth_project(project: &str) -> io::Result<CatalogMod> {
+    let client = Client::builder()
+        .user_agent(concat!("SubChunker/", env!("CARGO_PKG_VERSION")))
+        .build()
+        .map_err(io::Error::other)?;
+    let get_json = |url: String| -> io::Result<serde_json::Value> {
+        let response = client
+            .get(&url)
+            .send()
+            .map_err(io::Error::other)?;
+        if !response.status().is_success() {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("Modrinth project {} not found: {}", project, response.status()),
+            ));
+        }
+        response.json().map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    };
 
+    let info = get_json(format!("https://api.modrinth.com/v2/project/{}", project))?;
+    let slug = info["slug"].as_str().unwrap_or(project).to_string();
+    let name = info["title"].as_str().unwrap_or(&slug).replace(' ', "_");
+
+    let versions = get_json(format!("https://api.modrinth.com/v2/project/{}/version", slug))?;
+    let files = &versions[0]["files"];
+    let file = files
+        .as_array()
+        .and_then(|files| files.iter().find(|f| f["primary"].as_bool().unwrap_or(false)).or(files.first()))
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, format!("{} has no files", slug)))?;
+    let url = file["url"].as_str().unwrap_or_default();
+    let bytes = client
+        .get(url)
+        .send()
+        .and_then(|r| r.bytes())
+        .map_err(io::Error::other)?;
+    let installed = read_mod_from(Cursor::new(bytes))?;
+
+    Ok(CatalogMod {
+        name,
+        mod_id: installed.id,
+        source: ModSource::Modrinth { project: slug },
+        required: false,
+    })
+}
 
 #[derive(Deserialize)]
 struct FabricModJson {
     id: String,
+    version: Option<String>,
+    name: Option<String>,
+    environment: Option<String>,
+    #[serde(default)]
+    depends: BTreeMap<String, VersionRanges>,
+    #[serde(default)]
+    breaks: BTreeMap<String, VersionRanges>,
+    #[serde(default)]
+    provides: Vec<String>,
+    #[serde(default)]
+    jars: Vec<FabricNestedJar>,
 }
 
-pub fn get_mods(version: String) -> Vec<String> {
-    let mut output: Vec<String> = Vec::new();
-    let mods_folder = server_dir().join(&*version).join("mods");
-    if mods_folder.exists() {
-        for entry in fs::read_dir(&mods_folder).unwrap() {
-            if let Ok(entry) = entry {
-                if entry.file_name().to_str().unwrap().ends_with(".jar") {
-                    output.push(get_fabric_mod_id(entry.path()).unwrap());
-                }
+/// A version predicate, or a list of them of which any may match
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum VersionRanges {
+    One(String),
+    Any(Vec<String>),
+}
+
+impl VersionRanges {
+    fn into_vec(self) -> Vec<String> {
+        match self {
+            VersionRanges::One(range) => vec![range],
+            VersionRanges::Any(ranges) => ranges,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct FabricNestedJar {
+    file: String,
+}
+
+#[derive(Deserialize)]
+struct QuiltModJson {
+    quilt_loader: QuiltLoader,
+    minecraft: Option<QuiltMinecraft>,
+}
+
+#[derive(Deserialize)]
+struct QuiltLoader {
+    id: String,
+    version: Option<String>,
+    metadata: Option<QuiltMetadata>,
+    #[serde(default)]
+    depends: Vec<QuiltDependency>,
+    #[serde(default)]
+    breaks: Vec<QuiltDependency>,
+    #[serde(default)]
+    provides: Vec<QuiltDependency>,
+    #[serde(default)]
+    jars: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct QuiltMetadata {
+    name: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct QuiltMinecraft {
+    environment: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum QuiltDependency {
+    Id(String),
+    Object {
+        id: String,
+        versions: Option<VersionRanges>,
+        #[serde(default)]
+        optional: bool,
+    },
+}
+
+impl QuiltDependency {
+    fn id(&self) -> &str {
+        match self {
+            QuiltDependency::Id(id) => id,
+            QuiltDependency::Object { id, .. } => id,
+        }
+    }
+}
+
+/// Quilt dependency lists as `id -> ranges`, leaving out optional ones
+fn quilt_ranges(dependencies: Vec<QuiltDependency>) -> BTreeMap<String, Vec<String>> {
+    dependencies
+        .into_iter()
+        .filter_map(|dependency| match dependency {
+            QuiltDependency::Id(id) => Some((id, vec!["*".to_string()])),
+            QuiltDependency::Object { optional: true, .. } => None,
+            QuiltDependency::Object { id, versions, .. } => {
+                Some((id, versions.map(VersionRanges::into_vec).unwrap_or_else(|| vec!["*".to_string()])))
             }
+        })
+        .collect()
+}
+
+/// Metadata of a mod or plugin jar found in an instance
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct InstalledMod {
+    pub id: String,
+    pub version: Option<String>,
+    #[serde(default)]
+    pub name: Option<String>,
+    /// `*`, `client` or `server` for Fabric, `dedicated_server` for Quilt
+    #[serde(default)]
+    pub environment: Option<String>,
+    /// SHA-256 of the jar, `None` for mods nested in another jar
+    #[serde(default)]
+    pub sha256: Option<String>,
+    // Only needed to check the mods folder, so not stored with runs
+    /// Required mod IDs and their accepted version ranges
+    #[serde(skip)]
+    pub depends: BTreeMap<String, Vec<String>>,
+    /// Mod IDs and versions this mod refuses to run with
+    #[serde(skip)]
+    pub breaks: BTreeMap<String, Vec<String>>,
+    /// Extra IDs the mod answers to
+    #[serde(skip)]
+    pub provides: Vec<String>,
+    /// Mods bundled inside the jar under `META-INF/jars`
+    #[serde(skip)]
+    pub nested: Vec<InstalledMod>,
+}
+
+impl InstalledMod {
+    fn new(id: String, version: Option<String>) -> Self {
+        InstalledMod { id, version, ..Default::default() }
+    }
+
+    /// The mod followed by everything nested in it
+    pub fn flatten(&self) -> Vec<&InstalledMod> {
+        let mut mods = vec![self];
+        for nested in &self.nested {
+            mods.extend(nested.flatten());
         }
+        mods
+    }
+
+    pub fn display_name(&self) -> &str {
+        self.name.as_deref().unwrap_or(&self.id)
     }
-    output
 }
 
-fn get_fabric_mod_id<P: AsRef<Path>>(jar_path: P) -> io::Result<String> {
-    let file = File::open(jar_path)?;
-    let mut zip = ZipArchive::new(file)
-        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+/// The readable mods in an instance, plus the jars that couldn't be read
+pub struct ModScan {
+    pub mods: Vec<InstalledMod>,
+    /// `file: error` for every jar without usable metadata
+    pub unreadable: Vec<String>,
+}
 
-    let mut mod_json = zip
-        .by_name("fabric.mod.json")
-        .map_err(|_| {
-            io::Error::new(
-                io::ErrorKind::NotFound,
-                "fabric.mod.json not found in JAR",
-            )
-        })?;
+pub fn scan_mods(instance: &ServerInstance) -> ModScan {
+    let mut scan = ModScan { mods: Vec::new(), unreadable: Vec::new() };
+    let Ok(entries) = fs::read_dir(instance.mods_dir()) else {
+        return scan;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().is_none_or(|ext| ext != "jar") {
+            continue;
+        }
+        match read_mod(&path) {
+            Ok(mut installed) => {
+                installed.sha256 = sha256_file(&path).ok();
+                scan.mods.push(installed);
+            }
+            Err(e) => scan.unreadable.push(format!("{}: {}", entry.file_name().to_string_lossy(), e)),
+        }
+    }
+    scan
+}
+
+pub fn get_mods(instance: &ServerInstance) -> Vec<String> {
+    get_installed_mods(instance).into_iter().map(|m| m.id).collect()
+}
+
+pub fn get_installed_mods(instance: &ServerInstance) -> Vec<InstalledMod> {
+    scan_mods(instance).mods
+}
+
+// IDs supplied by the server itself rather than a jar in the mods folder
+const PLATFORM_IDS: [&str; 6] = ["minecraft", "java", "fabricloader", "fabric-loader", "quilt_loader", "neoforge"];
+
+/// Problems with the mods folder that are worth knowing before the server starts:
+/// unreadable jars, missing dependencies, conflicts and client-only mods
+pub fn mod_warnings(instance: &ServerInstance) -> Vec<String> {
+    let scan = scan_mods(instance);
+    let mut warnings: Vec<String> = scan.unreadable.iter().map(|e| format!("Could not read {}", e)).collect();
+
+    let all: Vec<&InstalledMod> = scan.mods.iter().flat_map(|m| m.flatten()).collect();
+    let present = |id: &str| {
+        PLATFORM_IDS.contains(&id) || all.iter().any(|m| m.id == id || m.provides.iter().any(|p| p == id))
+    };
+
+    for installed in &all {
+        for dependency in installed.depends.keys() {
+            if !present(dependency) {
+                warnings.push(format!("{} requires {}, which is not installed", installed.display_name(), dependency));
+            }
+        }
+        for (conflict, ranges) in &installed.breaks {
+            let clashing = all.iter().filter(|m| &m.id == conflict).any(|m| {
+                // Without a readable version, assume the worst
+                m.version.as_deref().and_then(|v| satisfies_any(v, ranges)).unwrap_or(true)
+            });
+            if clashing {
+                warnings.push(format!("{} conflicts with {} ({})", installed.display_name(), conflict, ranges.join(" || ")));
+            }
+        }
+        if installed.environment.as_deref() == Some("client") {
+            warnings.push(format!("{} is client-only", installed.display_name()));
+        }
+    }
+    warnings
+}
 
-    let mut json_str = String::new();
-    mod_json.read_to_string(&mut json_str)?;
+/// Mods whose `minecraft` or loader dependency excludes the instance's versions, as
+/// `name version needs minecraft 1.20.x (instance has 1.21.4)`
+pub fn incompatible_mods(instance: &ServerInstance) -> Vec<String> {
+    incompatible(instance, &scan_mods(instance).mods)
+}
 
-    let parsed: FabricModJson = serde_json::from_str(&json_str)
+fn incompatible(instance: &ServerInstance, mods: &[InstalledMod]) -> Vec<String> {
+    let loader_id = match instance.platform {
+        Platform::Fabric => "fabricloader",
+        Platform::Quilt => "quilt_loader",
+        _ => "",
+    };
+    let mut incompatible = Vec::new();
+    for installed in mods.iter().flat_map(|m| m.flatten()) {
+        for (dependency, ranges) in &installed.depends {
+            let have = if dependency == "minecraft" {
+                &instance.mc_ver
+            } else if dependency == loader_id {
+                &instance.loader_ver
+            } else {
+                continue;
+            };
+            // Versions that aren't SemVer, such as snapshots, can't be judged
+            if satisfies_any(have, ranges) == Some(false) {
+                incompatible.push(format!(
+                    "{} {} needs {} {} ({} has {})",
+                    installed.display_name(),
+                    installed.version.as_deref().unwrap_or("?"),
+                    dependency,
+                    ranges.join(" || "),
+                    instance.name(),
+                    have,
+                ));
+            }
+        }
+    }
+    incompatible
+}
+
+fn read_mod<P: AsRef<Path>>(jar_path: P) -> io::Result<InstalledMod> {
+    read_mod_from(File::open(jar_path)?)
+}
+
+/// Reads the metadata from whichever file the jar ships, including nested jars for Fabric and Quilt
+fn read_mod_from<R: Read + Seek>(jar: R) -> io::Result<InstalledMod> {
+    let mut zip = ZipArchive::new(jar)
         .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
 
-    Ok(parsed.id)
+    if let Some(json_str) = read_entry(&mut zip, "fabric.mod.json")? {
+        let parsed: FabricModJson = serde_json::from_str(&json_str)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        let nested = read_nested(&mut zip, parsed.jars.iter().map(|jar| jar.file.as_str()));
+        return Ok(InstalledMod {
+            id: parsed.id,
+            version: parsed.version,
+            name: parsed.name,
+            environment: parsed.environment,
+            depends: parsed.depends.into_iter().map(|(id, ranges)| (id, ranges.into_vec())).collect(),
+            breaks: parsed.breaks.into_iter().map(|(id, ranges)| (id, ranges.into_vec())).collect(),
+            provides: parsed.provides,
+            nested,
+            ..Default::default()
+        });
+    }
+
+    if let Some(json_str) = read_entry(&mut zip, "quilt.mod.json")? {
+        let parsed: QuiltModJson = serde_json::from_str(&json_str)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        let loader = parsed.quilt_loader;
+        let nested = read_nested(&mut zip, loader.jars.iter().map(String::as_str));
+        return Ok(InstalledMod {
+            id: loader.id,
+            version: loader.version,
+            name: loader.metadata.and_then(|metadata| metadata.name),
+            environment: parsed.minecraft.and_then(|minecraft| minecraft.environment),
+            provides: loader.provides.iter().map(|p| p.id().to_string()).collect(),
+            depends: quilt_ranges(loader.depends),
+            breaks: quilt_ranges(loader.breaks),
+            nested,
+            ..Default::default()
+        });
+    }
+
+    if let Some(toml_str) = read_entry(&mut zip, "META-INF/neoforge.mods.toml")?
+        && let Some(id) = find_value(&toml_str, "modId", '=')
+    {
+        return Ok(InstalledMod {
+            name: find_value(&toml_str, "displayName", '='),
+            ..InstalledMod::new(id, find_value(&toml_str, "version", '='))
+        });
+    }
+
+    for plugin_file in ["paper-plugin.yml", "plugin.yml"] {
+        if let Some(yml_str) = read_entry(&mut zip, plugin_file)?
+            && let Some(name) = find_value(&yml_str, "name", ':')
+        {
+            // Plugin names are display names such as "Chunky"
+            return Ok(InstalledMod {
+                name: Some(name.clone()),
+                ..InstalledMod::new(name.to_lowercase(), find_value(&yml_str, "version", ':'))
+            });
+        }
+    }
+
+    Err(io::Error::new(
+        io::ErrorKind::NotFound,
+        "No mod or plugin metadata found in JAR",
+    ))
+}
+
+/// Reads jars bundled inside another jar, skipping any that can't be read
+fn read_nested<'a, R: Read + Seek>(zip: &mut ZipArchive<R>, files: impl Iterator<Item = &'a str>) -> Vec<InstalledMod> {
+    files
+        .filter_map(|file| {
+            let mut entry = zip.by_name(file).ok()?;
+            let mut bytes = Vec::new();
+            entry.read_to_end(&mut bytes).ok()?;
+            read_mod_from(Cursor::new(bytes)).ok()
+        })
+        .collect()
+}
+
+fn read_entry<R: Read + Seek>(zip: &mut ZipArchive<R>, name: &str) -> io::Result<Option<String>> {
+    let mut entry = match zip.by_name(name) {
+        Ok(entry) => entry,
+        Err(_) => return Ok(None),
+    };
+
+    let mut contents = String::new();
+    entry.read_to_string(&mut contents)?;
+    Ok(Some(contents))
+}
+
+/// First `key = "value"` (TOML) or `key: value` (YAML) pair at the start of a line
+fn find_value(contents: &str, key: &str, separator: char) -> Option<String> {
+    contents.lines().find_map(|line| {
+        let (k, v) = line.trim().split_once(separator)?;
+        if k.trim() != key {
+            return None;
+        }
+        Some(v.trim().trim_matches(|c| c == '"' || c == '\'').to_string())
+    })
+}
+
+/// Modrinth versions page for a catalog mod, filtered to the instance
+pub fn get_url(mod_name: String, version: String, platform: Platform) -> Option<String> {
+    match &ModCatalog::load().get(&mod_name)?.source {
+        ModSource::Modrinth { project } => Some(format!(
+            "https://modrinth.com/project/{}/versions?g={}&l={}",
+            project, version, platform.modrinth_loader()
+        )),
+        ModSource::Local { .. } => None,
+    }
 }
 
-pub fn get_url(mod_name: String, version: String) -> String {
-    let index = if let Some(i) = MODS.iter().position(|x| x == &mod_name.as_str()) {
-        i
-    } else {
-        REQ_MODS.iter().position(|x| x == &mod_name.as_str()).unwrap() + MODS.len()
+/// Copies a local catalog jar into the instance's mods folder
+pub fn install_local_mod(mod_name: &str, instance: &ServerInstance) -> io::Result<bool> {
+    let catalog = ModCatalog::load();
+    let Some(ModSource::Local { path }) = catalog.get(mod_name).map(|m| &m.source) else {
+        return Ok(false);
     };
+    fs::create_dir_all(instance.mods_dir())?;
+    fs::copy(path, instance.mods_dir().join(path.file_name().unwrap_or_default()))?;
+    Ok(true)
+}
+
+/// Makes the mods folder hold exactly the selected and required mods. Jars that aren't wanted
+/// move to `disabled_mods_dir`, wanted ones move back from it. Returns the mods left active.
+pub fn sync_mods(instance: &ServerInstance, selected: &[String]) -> io::Result<Vec<InstalledMod>> {
+    let catalog = ModCatalog::load();
+    let mut wanted: Vec<String> = catalog.required(instance.platform).iter().map(|m| m.mod_id.clone()).collect();
+    wanted.extend(selected.iter().map(|name| catalog.mod_id(name)));
+    wanted.extend(InstanceManifest::load(instance).pack_mods);
+
+    let mods_dir = instance.mods_dir();
+    let disabled_dir = instance.disabled_mods_dir();
+    fs::create_dir_all(&mods_dir)?;
+    fs::create_dir_all(&disabled_dir)?;
+
+    // Restore wanted jars first so a disabled copy never shadows an active one
+    move_jars(&disabled_dir, &mods_dir, |id| wanted.contains(&id.to_string()))?;
+    move_jars(&mods_dir, &disabled_dir, |id| !wanted.contains(&id.to_string()))?;
+
+    Ok(get_installed_mods(instance))
+}
 
-    format!("{}?g={}&l=fabric", MOD_URLS[index], version)
+/// Moves every disabled jar back into the mods folder
+pub fn restore_mods(instance: &ServerInstance) -> io::Result<()> {
+    let disabled_dir = instance.disabled_mods_dir();
+    if !disabled_dir.exists() {
+        return Ok(());
+    }
+    move_jars(&disabled_dir, &instance.mods_dir(), |_| true)?;
+    if fs::read_dir(&disabled_dir)?.next().is_none() {
+        fs::remove_dir(&disabled_dir)?;
+    }
+    Ok(())
 }
 
-pub fn is_mod_installed(mod_name: String, version: String) -> bool {
-    let installed_mods = get_mods(version.to_string());
-    installed_mods.contains(&mod_name.to_lowercase())
-}
\ No newline at end of file
+/// Moves the jars in `from` whose mod ID matches `filter`. Unreadable jars and name clashes are left in place.
+fn move_jars(from: &Path, to: &Path, filter: impl Fn(&str) -> bool) -> io::Result<()> {
+    for entry in fs::read_dir(from)?.flatten() {
+        let path = entry.path();
+        if path.extension().is_none_or(|ext| ext != "jar") {
+            continue;
+        }
+        let id = match read_mod(&path) {
+            Ok(installed) => installed.id,
+            Err(e) => {
+                warn!("Skipping {}: {}", path.display(), e);
+                continue;
+            }
+        };
+        let target = to.join(entry.file_name());
+        if filter(&id) && !target.exists() {
+            fs::rename(&path, target)?;
+        }
+    }
+    Ok(())
+}
+
+pub fn is_mod_installed(mod_name: String, instance: &ServerInstance) -> bool {
+    let installed_mods = get_mods(instance);
+    installed_mods.contains(&ModCatalog::load().mod_id(&mod_name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use zip::write::SimpleFileOptions;
+    use zip::{CompressionMethod, ZipWriter};
+
+    fn jar(files: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, contents) in files {
+            zip.start_file(*name, SimpleFileOptions::default().compression_method(CompressionMethod::Stored)).unwrap();
+            zip.write_all(contents).unwrap();
+        }
+        zip.finish().unwrap().into_inner()
+    }
+
+    fn read(files: &[(&str, &[u8])]) -> io::Result<InstalledMod> {
+        read_mod_from(Cursor::new(jar(files)))
+    }
+
+    fn fabric(json: &str) -> InstalledMod {
+        read(&[("fabric.mod.json", json.as_bytes())]).unwrap()
+    }
+
+    #[test]
+    fn fabric_metadata() {
+        let installed = fabric(
+            r#"{
+                "schemaVersion": 1, "id": "lithium", "version": "0.15.0", "name": "Lithium", "environment": "*",
+                "depends": {"minecraft": "1.21.x", "fabricloader": [">=0.16", "<0.15"]},
+                "breaks": {"optifabric": "*"},
+                "provides": ["lithium-api"]
+            }"#,
+        );
+        assert_eq!(installed.id, "lithium");
+        assert_eq!(installed.version.as_deref(), Some("0.15.0"));
+        assert_eq!(installed.display_name(), "Lithium");
+        assert_eq!(installed.environment.as_deref(), Some("*"));
+        assert_eq!(installed.depends["minecraft"], ["1.21.x"]);
+        assert_eq!(installed.depends["fabricloader"], [">=0.16", "<0.15"]);
+        assert_eq!(installed.breaks["optifabric"], ["*"]);
+        assert_eq!(installed.provides, ["lithium-api"]);
+    }
+
+    #[test]
+    fn nested_jars() {
+        let inner = jar(&[("fabric.mod.json", br#"{"id": "fabric-api-base", "version": "0.4.0"}"#)]);
+        let broken = b"not a zip".to_vec();
+        let outer = jar(&[
+            ("fabric.mod.json", br#"{"id": "fabric-api", "version": "0.110.0", "jars": [{"file": "META-INF/jars/base.jar"}, {"file": "META-INF/jars/broken.jar"}, {"file": "META-INF/jars/missing.jar"}]}"#),
+            ("META-INF/jars/base.jar", &inner),
+            ("META-INF/jars/broken.jar", &broken),
+        ]);
+        let installed = read_mod_from(Cursor::new(outer)).unwrap();
+        // Unreadable and missing nested jars are skipped
+        let ids: Vec<&str> = installed.flatten().iter().map(|m| m.id.as_str()).collect();
+        assert_eq!(ids, ["fabric-api", "fabric-api-base"]);
+        assert_eq!(installed.nested[0].version.as_deref(), Some("0.4.0"));
+    }
+
+    #[test]
+    fn quilt_metadata() {
+        let installed = read(&[(
+            "quilt.mod.json",
+            br#"{
+                "schema_version": 1,
+                "quilt_loader": {
+                    "id": "qsl", "version": "10.0.0", "metadata": {"name": "Quilt Standard Libraries"},
+                    "depends": ["quilt_loader", {"id": "minecraft", "versions": ">=1.21"}, {"id": "sodium", "optional": true}],
+                    "breaks": [{"id": "old_qsl", "versions": ["<9", "9.0.0"]}],
+                    "provides": ["quilted_fabric_api", {"id": "fabric-api"}]
+                },
+                "minecraft": {"environment": "dedicated_server"}
+            }"#,
+        )])
+        .unwrap();
+        assert_eq!(installed.id, "qsl");
+        assert_eq!(installed.display_name(), "Quilt Standard Libraries");
+        assert_eq!(installed.environment.as_deref(), Some("dedicated_server"));
+        // Optional dependencies are left out
+        assert_eq!(installed.depends.keys().collect::<Vec<_>>(), ["minecraft", "quilt_loader"]);
+        assert_eq!(installed.depends["quilt_loader"], ["*"]);
+        assert_eq!(installed.depends["minecraft"], [">=1.21"]);
+        assert_eq!(installed.breaks["old_qsl"], ["<9", "9.0.0"]);
+        assert_eq!(installed.provides, ["quilted_fabric_api", "fabric-api"]);
+    }
+
+    #[test]
+    fn neoforge_metadata() {
+        let toml = "modLoader=\"javafml\"\n[[mods]]\n    modId = \"ferritecore\"\n    version = \"7.1.1\"\n    displayName='FerriteCore'\n";
+        let installed = read(&[("META-INF/neoforge.mods.toml", toml.as_bytes())]).unwrap();
+        assert_eq!(installed.id, "ferritecore");
+        assert_eq!(installed.version.as_deref(), Some("7.1.1"));
+        assert_eq!(installed.display_name(), "FerriteCore");
+    }
+
+    #[test]
+    fn plugin_metadata() {
+        let yml = "name: Chunky\nversion: '1.4.28'\nmain: org.popcraft.chunky.ChunkyBukkit\n";
+        let installed = read(&[("plugin.yml", yml.as_bytes())]).unwrap();
+        assert_eq!(installed.id, "chunky");
+        assert_eq!(installed.version.as_deref(), Some("1.4.28"));
+        assert_eq!(installed.display_name(), "Chunky");
+        // Paper's own format wins over the legacy file
+        let installed = read(&[("plugin.yml", b"name: Old\n"), ("paper-plugin.yml", b"name: New\n")]).unwrap();
+        assert_eq!(installed.id, "new");
+    }
+
+    #[test]
+    fn no_metadata() {
+        let kind = |result: io::Result<InstalledMod>| result.err().map(|e| e.kind());
+        assert_eq!(kind(read(&[("README.md", b"# A library")])), Some(io::ErrorKind::NotFound));
+        assert_eq!(kind(read_mod_from(Cursor::new(b"not a zip".to_vec()))), Some(io::ErrorKind::InvalidData));
+        assert_eq!(kind(read(&[("fabric.mod.json", b"{")])), Some(io::ErrorKind::InvalidData));
+    }
+
+    #[test]
+    fn incompatible_versions() {
+        let instance = ServerInstance::new(Platform::Fabric, "1.21.4", "0.16.10");
+        let mut outdated = fabric(r#"{"id": "old", "version": "1.0", "name": "Old Mod", "depends": {"minecraft": "~1.20"}}"#);
+        outdated.nested.push(fabric(r#"{"id": "inner", "version": "2.0", "depends": {"fabricloader": ">=0.17"}}"#));
+        let mods = [
+            outdated,
+            fabric(r#"{"id": "fine", "depends": {"minecraft": ["1.20.x", "1.21.x"], "fabricloader": ">=0.16", "lithium": "<0.1"}}"#),
+            // Snapshot ranges aren't SemVer and can't be judged
+            fabric(r#"{"id": "snapshot", "depends": {"minecraft": "24w14a"}}"#),
+        ];
+        assert_eq!(
+            incompatible(&instance, &mods),
+            [
+                format!("Old Mod 1.0 needs minecraft ~1.20 ({} has 1.21.4)", instance.name()),
+                format!("inner 2.0 needs fabricloader >=0.17 ({} has 0.16.10)", instance.name()),
+            ]
+        );
+        // Paper has no loader dependency to check
+        let paper = ServerInstance::new(Platform::Paper, "1.21.4", "100");
+        assert!(incompatible(&paper, &mods[1..]).is_empty());
+    }
+}
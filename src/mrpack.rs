@@ -0,0 +1,210 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{Read, Seek};
+use std::path::{Component, Path, PathBuf};
+use std::sync::mpsc::Sender;
+use std::{fs, io};
+use serde::Deserialize;
+use log::error;
+use zip::ZipArchive;
+use crate::io::{download_file, install_server, server_dir, InstallerMsg, Platform, ServerInstance};
+use crate::manifest::{sha512_file, InstanceManifest};
+use crate::mods::get_mods;
+
+pub const MRPACK_INDEX: &str = "modrinth.index.json";
+
+/// `modrinth.index.json` from a Modrinth modpack
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MrpackIndex {
+    pub name: String,
+    pub version_id: String,
+    pub files: Vec<MrpackFile>,
+    /// `minecraft` plus one of `fabric-loader`, `quilt-loader` or `neoforge`
+    pub dependencies: HashMap<String, String>,
+}
+
+#[derive(Deserialize)]
+pub struct MrpackFile {
+    pub path: String,
+    pub hashes: HashMap<String, String>,
+    pub env: Option<MrpackEnv>,
+    pub downloads: Vec<String>,
+}
+
+#[derive(Deserialize)]
+pub struct MrpackEnv {
+    /// `required`, `optional` or `unsupported`
+    pub server: String,
+}
+
+impl MrpackFile {
+    fn on_server(&self) -> bool {
+        self.env.as_ref().is_none_or(|env| env.server != "unsupported")
+    }
+}
+
+impl MrpackIndex {
+    /// The instance the pack runs on, picked from its `dependencies`
+    pub fn instance(&self) -> io::Result<ServerInstance> {
+        let mc_ver = self.dependencies.get("minecraft").ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidData, "Modpack does not name a Minecraft version")
+        })?;
+        let (platform, loader_ver) = [
+            (Platform::Fabric, "fabric-loader"),
+            (Platform::Quilt, "quilt-loader"),
+            (Platform::NeoForge, "neoforge"),
+        ]
+        .into_iter()
+        .find_map(|(platform, key)| self.dependencies.get(key).map(|ver| (platform, ver)))
+        .ok_or_else(|| {
+            io::Error::new(io::ErrorKind::Unsupported, "Modpack needs Fabric, Quilt or NeoForge")
+        })?;
+
+        let mut instance = ServerInstance::new(platform, mc_ver, loader_ver);
+        instance.pack = pack_slug(&self.name);
+        Ok(instance)
+    }
+}
+
+/// Lowercase name safe to use in a folder name
+fn pack_slug(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '_' })
+        .collect()
+}
+
+fn read_index<R: Read + Seek>(zip: &mut ZipArchive<R>) -> io::Result<MrpackIndex> {
+    let entry = zip
+        .by_name(MRPACK_INDEX)
+        .map_err(|_| io::Error::new(io::ErrorKind::NotFound, format!("No {} in modpack", MRPACK_INDEX)))?;
+    serde_json::from_reader(entry).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+/// Installs the pack's server into its own instance: the server itself, every server side file
+/// checked against its SHA-512, then `overrides/` and `server-overrides/`
+pub fn import_mrpack(path: &Path, jvm: &str, memory_mb: u32, sender: &Sender<InstallerMsg>) -> io::Result<ServerInstance> {
+    let mut zip = ZipArchive::new(File::open(path)?)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    let index = read_index(&mut zip)?;
+    let instance = index.instance()?;
+
+    install_server(&instance, jvm, memory_mb, sender)?;
+
+    // Files
+    let files: Vec<&MrpackFile> = index.files.iter().filter(|file| file.on_server()).collect();
+    for (i, file) in files.iter().enumerate() {
+        sender.send(InstallerMsg::InstallingMsg(format!("Downloading {}", file.path))).ok();
+        sender.send(InstallerMsg::Progress(i as f32 / files.len() as f32)).ok();
+        install_pack_file(file, &pack_path(&instance.dir(), &file.path)?)?;
+    }
+
+    // Overrides, server specific ones last so they win
+    sender.send(InstallerMsg::InstallingMsg("Applying overrides".to_string())).ok();
+    for prefix in ["overrides/", "server-overrides/"] {
+        extract_overrides(&mut zip, prefix, &instance.dir())?;
+    }
+
+    // Remember the pack's mods so syncing the mods folder leaves them in place
+    let mut manifest = InstanceManifest::load(&instance);
+    manifest.pack = instance.pack.clone();
+    manifest.pack_name = format!("{} {}", index.name, index.version_id);
+    manifest.pack_mods = get_mods(&instance);
+    manifest.save(&instance)?;
+
+    sender.send(InstallerMsg::Progress(1.0)).ok();
+    Ok(instance)
+}
+
+/// Downloads a pack file unless an identical copy is already there, trying each mirror in turn
+fn install_pack_file(file: &MrpackFile, target: &Path) -> io::Result<()> {
+    let sha512 = file.hashes.get("sha512").ok_or_else(|| {
+        io::Error::new(io::ErrorKind::InvalidData, format!("{} has no SHA-512 hash", file.path))
+    })?;
+    if sha512_file(target).is_ok_and(|hash| &hash == sha512) {
+        return Ok(());
+    }
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    for url in &file.downloads {
+        if let Err(e) = download_file(url, target) {
+            error!("Failed to download {}: {}", url, e);
+            continue;
+        }
+        if sha512_file(target)? == *sha512 {
+            return Ok(());
+        }
+        error!("Hash mismatch for {}", url);
+    }
+    fs::remove_file(target).ok();
+    Err(io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("No download of {} matched its hash", file.path),
+    ))
+}
+
+/// Resolves a pack relative path, refusing anything that would land outside the instance
+fn pack_path(dir: &Path, relative: &str) -> io::Result<PathBuf> {
+    let relative = Path::new(relative);
+    if !relative.components().all(|c| matches!(c, Component::Normal(_))) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Unsafe path in modpack: {}", relative.display()),
+        ));
+    }
+    Ok(dir.join(relative))
+}
+
+fn extract_overrides<R: Read + Seek>(zip: &mut ZipArchive<R>, prefix: &str, dir: &Path) -> io::Result<()> {
+    for i in 0..zip.len() {
+        let mut entry = zip.by_index(i).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        let Some(name) = entry.enclosed_name() else {
+            continue;
+        };
+        let Ok(relative) = name.strip_prefix(prefix) else {
+            continue;
+        };
+        if relative.as_os_str().is_empty() {
+            continue;
+        }
+
+        let target = dir.join(relative);
+        if entry.is_dir() {
+            fs::create_dir_all(&target)?;
+        } else {
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            io::copy(&mut entry, &mut File::create(&target)?)?;
+        }
+    }
+    Ok(())
+}
+
+/// Instances created from modpacks, found through their manifests
+pub fn imported_packs() -> Vec<ServerInstance> {
+    let Ok(entries) = fs::read_dir(server_dir()) else {
+        return Vec::new();
+    };
+    let mut packs: Vec<ServerInstance> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let manifest = InstanceManifest::read(&entry.path()).ok()?;
+            if manifest.pack.is_empty() {
+                return None;
+            }
+            let mut instance = ServerInstance::new(
+                Platform::from_name(&manifest.platform)?,
+                &manifest.mc_ver,
+                &manifest.loader_ver,
+            );
+            instance.installer_ver = manifest.installer_ver;
+            instance.pack = manifest.pack;
+            Some(instance)
+        })
+        .collect();
+    packs.sort_by(|a, b| a.pack.cmp(&b.pack));
+    packs
+}
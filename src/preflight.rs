@@ -0,0 +1,150 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::thread;
+use std::time::Duration;
+use hwinfo_rs::hwinfo;
+use crate::io::server_dir;
+use crate::memory::ram_warnings;
+
+// Below this the world of a large radius may not fit
+const MIN_FREE_DISK_GB: f64 = 5.0;
+// A process using more than this share of one core competes with the server
+const HEAVY_PROCESS_CPU: f64 = 0.5;
+const PROCESS_SAMPLE: Duration = Duration::from_millis(500);
+// Swap in use beyond this is worth mentioning
+const SWAP_WARNING_MB: u64 = 256;
+
+/// Conditions that make a run's numbers unreliable, checked right before the server starts.
+/// Checks that can't read their source are skipped.
+pub fn preflight_checks(memory_mb: u32) -> Vec<String> {
+    let mut warnings = Vec::new();
+    warnings.extend(governor_warning());
+    warnings.extend(battery_warning());
+    warnings.extend(throttle_warning());
+    warnings.extend(ram_warnings(memory_mb));
+    warnings.extend(swap_warning());
+    warnings.extend(disk_warning(&server_dir()));
+    warnings.extend(heavy_processes());
+    warnings
+}
+
+fn governor_warning() -> Option<String> {
+    let entries = fs::read_dir("/sys/devices/system/cpu").ok()?;
+    let mut governors: Vec<String> = entries
+        .flatten()
+        .filter_map(|entry| fs::read_to_string(entry.path().join("cpufreq/scaling_governor")).ok())
+        .map(|governor| governor.trim().to_string())
+        .filter(|governor| governor != "performance")
+        .collect();
+    governors.sort();
+    governors.dedup();
+    if governors.is_empty() {
+        return None;
+    }
+    Some(format!(
+        "CPU frequency governor is {}, switch to performance for steady clocks",
+        governors.join(", ")
+    ))
+}
+
+fn battery_warning() -> Option<String> {
+    let entries = fs::read_dir("/sys/class/power_supply").ok()?;
+    let mut has_battery = false;
+    let mut on_mains = false;
+    for entry in entries.flatten() {
+        let read = |name: &str| fs::read_to_string(entry.path().join(name)).map(|s| s.trim().to_string()).unwrap_or_default();
+        match read("type").as_str() {
+            "Battery" => has_battery = true,
+            "Mains" => on_mains |= read("online") == "1",
+            _ => {}
+        }
+    }
+    (has_battery && !on_mains).then(|| "Running on battery power, plug in for full performance".to_string())
+}
+
+/// Intel exposes how often each core and package hit their thermal limit since boot
+fn throttle_warning() -> Option<String> {
+    let entries = fs::read_dir("/sys/devices/system/cpu").ok()?;
+    let count: u64 = entries
+        .flatten()
+        .filter_map(|entry| fs::read_to_string(entry.path().join("thermal_throttle/core_throttle_count")).ok())
+        .filter_map(|count| count.trim().parse::<u64>().ok())
+        .sum();
+    (count > 0).then(|| format!("CPU cores were thermally throttled {} times since boot, check cooling", count))
+}
+
+fn swap_warning() -> Option<String> {
+    let meminfo = fs::read_to_string("/proc/meminfo").ok()?;
+    let value = |key: &str| {
+        meminfo
+            .lines()
+            .find_map(|line| line.strip_prefix(key)?.trim().strip_suffix("kB")?.trim().parse::<u64>().ok())
+    };
+    let used_mb = value("SwapTotal:")?.saturating_sub(value("SwapFree:")?) / 1024;
+    (used_mb > SWAP_WARNING_MB).then(|| format!("{} MB of swap is in use, the heap may be paged out", used_mb))
+}
+
+/// Free space on the disk whose mount point is the longest prefix of `dir`
+fn disk_warning(dir: &Path) -> Option<String> {
+    let dir = fs::canonicalize(dir).ok()?;
+    let disks = hwinfo::disks().ok()?;
+    let (_, free_bytes) = disks
+        .iter()
+        .flat_map(|disk| disk.volumes.iter().map(move |volume| (volume, disk.free_size_bytes)))
+        .filter(|(volume, _)| !volume.is_empty() && dir.starts_with(volume.as_str()))
+        .max_by_key(|(volume, _)| volume.len())?;
+    let free = free_bytes as f64 / 1_073_741_824.0;
+    (free < MIN_FREE_DISK_GB).then(|| format!("Only {:.1} GB of disk space is free in {}", free, dir.display()))
+}
+
+/// Processes that used more than half a core while sampling, busiest first
+fn heavy_processes() -> Option<String> {
+    let before = process_cpu_ticks();
+    if before.is_empty() {
+        return None;
+    }
+    thread::sleep(PROCESS_SAMPLE);
+    let after = process_cpu_ticks();
+
+    // Kernel clock ticks are 100 per second on every mainstream Linux build
+    let ticks = PROCESS_SAMPLE.as_secs_f64() * 100.0;
+    let own_pid = std::process::id().to_string();
+    let mut heavy: Vec<(String, f64)> = after
+        .iter()
+        .filter(|(pid, _)| **pid != own_pid)
+        .filter_map(|(pid, (name, used))| {
+            let share = used.saturating_sub(before.get(pid)?.1) as f64 / ticks;
+            (share > HEAVY_PROCESS_CPU).then(|| (name.clone(), share))
+        })
+        .collect();
+    if heavy.is_empty() {
+        return None;
+    }
+    heavy.sort_by(|a, b| b.1.total_cmp(&a.1));
+    let names: Vec<String> = heavy.iter().map(|(name, share)| format!("{} ({:.0}%)", name, share * 100.0)).collect();
+    Some(format!("Other processes are busy: {}", names.join(", ")))
+}
+
+/// Name and user plus system CPU ticks of every process, by PID
+fn process_cpu_ticks() -> HashMap<String, (String, u64)> {
+    let Ok(entries) = fs::read_dir("/proc") else {
+        return HashMap::new();
+    };
+    entries
+        .flatten()
+        .filter_map(|entry| {
+            let pid = entry.file_name().to_string_lossy().to_string();
+            if !pid.chars().all(|c| c.is_ascii_digit()) {
+                return None;
+            }
+            let stat = fs::read_to_string(entry.path().join("stat")).ok()?;
+            // The name is in parentheses and may itself contain spaces
+            let (name, rest) = stat.split_once(" (")?.1.rsplit_once(") ")?;
+            let fields: Vec<&str> = rest.split_whitespace().collect();
+            let utime = fields.get(11)?.parse::<u64>().ok()?;
+            let stime = fields.get(12)?.parse::<u64>().ok()?;
+            Some((pid, (name.to_string(), utime + stime)))
+        })
+        .collect()
+}
@@ -0,0 +1,244 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use serde::{Deserialize, Serialize};
+use slint::SharedString;
+use crate::benchmark::{Iterations, LaunchOptions};
+use crate::java::gc_thread_flags;
+use crate::io::{presets_dir, Platform, ServerInstance};
+use crate::mods::{get_installed_mods, ModCatalog};
+use crate::mrpack::imported_packs;
+use crate::slint_utils::{checked_names, toggled_names};
+use crate::Info;
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct PresetMod {
+    pub name: String,
+    /// Version of the installed jar when the preset was saved
+    pub version: Option<String>,
+}
+
+/// A named benchmark configuration that can be shared between machines
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Preset {
+    pub name: String,
+    pub minecraft_version: String,
+    pub platform: String,
+    pub loader_version: String,
+    /// Slug of the modpack the preset was saved from, empty for plain instances.
+    /// The pack has to be imported on the machine running the preset.
+    #[serde(default)]
+    pub pack: String,
+    pub jvm: String,
+    pub gcs: Vec<String>,
+    pub args: Vec<String>,
+    /// Heap size in MB
+    #[serde(default)]
+    pub ram_alloc_mb: u32,
+    /// Heap size in GB, only read from presets saved before sizes were in MB
+    #[serde(default, skip_serializing)]
+    ram_alloc: u32,
+    pub mods: Vec<PresetMod>,
+    pub seed: String,
+    pub radius: i32,
+    /// Runs per configuration; older presets without it run once
+    #[serde(default)]
+    pub iterations: u32,
+    /// Seconds between iterations
+    #[serde(default)]
+    pub cooldown: u32,
+    /// Logical CPUs the server is limited to, all when missing
+    #[serde(default)]
+    pub cores: Option<u32>,
+    #[serde(default)]
+    pub gc_threads: Option<u32>,
+    #[serde(default)]
+    pub c2me_threads: Option<u32>,
+}
+
+impl Preset {
+    /// Captures the Configure panel, pinning mods to the versions installed in the selected instance
+    pub fn from_info(name: &str, info: &Info) -> Self {
+        let instance = ServerInstance::from_info(info);
+        let installed = get_installed_mods(&instance);
+        let catalog = ModCatalog::load();
+        let mods = toggled_names(&info.get_recommended_mod_list(), &info.get_recommended_mods_toggle())
+            .into_iter()
+            .map(|name| {
+                let mod_id = catalog.mod_id(&name);
+                let version = installed
+                    .iter()
+                    .find(|m| m.id == mod_id)
+                    .and_then(|m| m.version.clone());
+                PresetMod { name, version }
+            })
+            .collect();
+
+        Preset {
+            name: name.trim().to_string(),
+            minecraft_version: instance.mc_ver,
+            platform: instance.platform.name().to_string(),
+            loader_version: instance.loader_ver,
+            pack: instance.pack,
+            jvm: info.get_selected_jvm().to_string(),
+            gcs: toggled_names(&info.get_gcs(), &info.get_gcs_toggle()),
+            args: toggled_names(&info.get_args(), &info.get_args_toggle()),
+            ram_alloc_mb: info.get_ram_alloc().max(0) as u32,
+            ram_alloc: 0,
+            mods,
+            seed: info.get_seed().to_string(),
+            radius: info.get_radius(),
+            iterations: info.get_iterations().max(1) as u32,
+            cooldown: info.get_cooldown().max(0) as u32,
+            cores: (info.get_cores() > 0).then_some(info.get_cores() as u32),
+            gc_threads: (info.get_gc_threads() > 0).then_some(info.get_gc_threads() as u32),
+            c2me_threads: (info.get_c2me_threads() > 0).then_some(info.get_c2me_threads() as u32),
+        }
+    }
+
+    /// Loads the preset into the Configure panel. The loader list still has to be refreshed
+    /// for the new platform and Minecraft version afterwards.
+    pub fn apply_to(&self, info: &Info) {
+        let mod_names: Vec<String> = self.mods.iter().map(|m| m.name.clone()).collect();
+        info.set_selected_minecraft_version(SharedString::from(&self.minecraft_version));
+        info.set_selected_platform(SharedString::from(&self.platform));
+        info.set_selected_loader_version(SharedString::from(&self.loader_version));
+        info.set_selected_jvm(SharedString::from(&self.jvm));
+        info.set_gcs_toggle(checked_names(&info.get_gcs(), &self.gcs));
+        info.set_args_toggle(checked_names(&info.get_args(), &self.args));
+        info.set_recommended_mods_toggle(checked_names(&info.get_recommended_mod_list(), &mod_names));
+        info.set_ram_alloc(self.ram_mb() as i32);
+        info.set_seed(SharedString::from(&self.seed));
+        info.set_radius(self.radius);
+        info.set_iterations(self.iterations.max(1) as i32);
+        info.set_cooldown(self.cooldown as i32);
+        info.set_cores(self.cores.unwrap_or(0) as i32);
+        info.set_gc_threads(self.gc_threads.unwrap_or(0) as i32);
+        info.set_c2me_threads(self.c2me_threads.unwrap_or(0) as i32);
+    }
+
+    pub fn ram_mb(&self) -> u32 {
+        if self.ram_alloc_mb > 0 { self.ram_alloc_mb } else { self.ram_alloc.max(1) * 1024 }
+    }
+
+    pub fn iterations(&self) -> Iterations {
+        Iterations { count: self.iterations.max(1), cooldown: self.cooldown }
+    }
+
+    pub fn instance(&self) -> io::Result<ServerInstance> {
+        let platform = Platform::from_name(&self.platform).ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidData, format!("Unknown platform {}", self.platform))
+        })?;
+        if self.pack.is_empty() {
+            return Ok(ServerInstance::new(platform, &self.minecraft_version, &self.loader_version));
+        }
+        imported_packs().into_iter().find(|instance| instance.pack == self.pack).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("Modpack {} isn't imported, import its .mrpack first", self.pack),
+            )
+        })
+    }
+
+    /// One launch per selected garbage collector, labelled with its name
+    pub fn launch_options(&self) -> Vec<(String, LaunchOptions)> {
+        let mods: Vec<String> = match Platform::from_name(&self.platform) {
+            Some(platform) if platform.supports_mods() => self.mods.iter().map(|m| m.name.clone()).collect(),
+            _ => Vec::new(),
+        };
+        let options = |gc: Option<&str>| {
+            let mut options = LaunchOptions {
+                mods: Some(mods.clone()),
+                cores: self.cores,
+                c2me_threads: self.c2me_threads,
+                ..LaunchOptions::new(&self.jvm, self.ram_mb(), gc, &self.args, &self.seed, self.radius)
+            };
+            if let Some(threads) = self.gc_threads {
+                options.args.extend(gc_thread_flags(threads));
+            }
+            options
+        };
+        if self.gcs.is_empty() {
+            return vec![("Default GC".to_string(), options(None))];
+        }
+        self.gcs.iter().map(|gc| (gc.clone(), options(Some(gc)))).collect()
+    }
+
+    /// Pinned mods whose installed version differs, as `name (wanted, installed)`
+    pub fn pin_mismatches(&self, instance: &ServerInstance) -> Vec<String> {
+        let installed = get_installed_mods(instance);
+        let catalog = ModCatalog::load();
+        self.mods
+            .iter()
+            .filter_map(|m| {
+                let wanted = m.version.as_ref()?;
+                let mod_id = catalog.mod_id(&m.name);
+                let found = installed.iter().find(|i| i.id == mod_id)?;
+                let found_version = found.version.clone().unwrap_or_default();
+                if &found_version == wanted {
+                    None
+                } else {
+                    Some(format!("{} ({} wanted, {} installed)", m.name, wanted, found_version))
+                }
+            })
+            .collect()
+    }
+
+    pub fn save(&self) -> io::Result<PathBuf> {
+        if self.name.is_empty() {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "Preset needs a name"));
+        }
+        self.write(&presets_dir())
+    }
+
+    /// Writes `<name>.json` into `dir`
+    pub fn write(&self, dir: &Path) -> io::Result<PathBuf> {
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        let path = dir.join(format!("{}.json", file_name(&self.name)));
+        fs::write(&path, json)?;
+        Ok(path)
+    }
+
+    pub fn read(path: &Path) -> io::Result<Preset> {
+        let json = fs::read_to_string(path)?;
+        serde_json::from_str(&json).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    /// Loads a saved preset by name, or a preset file by path
+    pub fn load(name_or_path: &str) -> io::Result<Preset> {
+        let saved = presets_dir().join(format!("{}.json", file_name(name_or_path)));
+        if saved.is_file() {
+            return Preset::read(&saved);
+        }
+        Preset::read(Path::new(name_or_path))
+    }
+
+    /// Copies a preset file from another machine into the presets folder
+    pub fn import(path: &Path) -> io::Result<Preset> {
+        let preset = Preset::read(path)?;
+        preset.save()?;
+        Ok(preset)
+    }
+}
+
+/// Names of all saved presets, sorted
+pub fn preset_names() -> Vec<String> {
+    let mut output: Vec<String> = Vec::new();
+    if let Ok(entries) = fs::read_dir(presets_dir()) {
+        for entry in entries.flatten() {
+            if let Ok(preset) = Preset::read(&entry.path()) {
+                output.push(preset.name);
+            }
+        }
+    }
+    output.sort();
+    output
+}
+
+fn file_name(name: &str) -> String {
+    name.trim()
+        .chars()
+        .map(|c| if c.is_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect()
+}
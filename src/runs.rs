@@ -0,0 +1,209 @@
+use std::fs;
+use std::io;
+use std::time::{SystemTime, UNIX_EPOCH};
+use serde::{Deserialize, Serialize};
+use crate::benchmark::LaunchOptions;
+use crate::gc_log::GcSummary;
+use crate::java::JvmHeap;
+use crate::jfr::JfrSummary;
+use crate::io::{runs_file, ServerInstance};
+use crate::mods::InstalledMod;
+use crate::stats::{mean, outliers, stddev};
+use crate::system_info::SystemInfo;
+
+/// A finished benchmark, stored in `data/benchmarks.json`
+#[derive(Serialize, Deserialize, Clone)]
+pub struct BenchmarkRun {
+    pub id: String,
+    /// What distinguishes the run within a batch, e.g. the GC or `+Lithium`
+    #[serde(default)]
+    pub label: String,
+    /// ID of the first run of the batch this run belongs to
+    #[serde(default)]
+    pub batch: String,
+    /// 1-based position among the repeats of the same configuration
+    #[serde(default)]
+    pub iteration: u32,
+    /// Seconds since the Unix epoch
+    pub timestamp: u64,
+    pub instance: String,
+    pub platform: String,
+    pub minecraft_version: String,
+    pub loader_version: String,
+    pub jvm: String,
+    /// Heap size in MB
+    #[serde(default)]
+    pub memory_mb: u32,
+    /// Heap size in GB, only read from runs stored before sizes were in MB
+    #[serde(default, skip_serializing)]
+    memory: u32,
+    /// The heap the JVM settled on
+    #[serde(default)]
+    pub heap: Option<JvmHeap>,
+    /// JVM flags, including the garbage collector
+    pub args: Vec<String>,
+    pub seed: String,
+    pub radius: i32,
+    /// Logical CPUs the server was limited to, `None` for all of them
+    #[serde(default)]
+    pub cores: Option<u32>,
+    #[serde(default)]
+    pub c2me_threads: Option<u32>,
+    pub average_cps: f32,
+    /// Every CPS reading Chunky printed during the run
+    #[serde(default)]
+    pub samples: Vec<f32>,
+    /// Mods the server actually loaded
+    pub mods: Vec<InstalledMod>,
+    /// The machine the run happened on
+    #[serde(default)]
+    pub system: Option<SystemInfo>,
+    /// Collections and pauses from the run's GC log
+    #[serde(default)]
+    pub gc: Option<GcSummary>,
+    /// Imported from another machine's result bundle
+    #[serde(default)]
+    pub foreign: bool,
+    /// Warnings from the checks before launch, such as battery power or a powersave governor
+    #[serde(default)]
+    pub preflight: Vec<String>,
+    /// Hot methods, allocations and thread states of a run profiled with JFR. The recording
+    /// itself is kept in `data/runs/<id>/profile.jfr`.
+    #[serde(default)]
+    pub profile: Option<JfrSummary>,
+}
+
+impl BenchmarkRun {
+    pub fn new(instance: &ServerInstance, options: &LaunchOptions, average_cps: f32, samples: Vec<f32>, mods: Vec<InstalledMod>) -> Self {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+        // Milliseconds keep IDs unique even when failed runs end right away
+        let id = now.as_millis().to_string();
+        BenchmarkRun {
+            batch: id.clone(),
+            id,
+            label: String::new(),
+            iteration: 1,
+            timestamp: now.as_secs(),
+            instance: instance.name(),
+            platform: instance.platform.name().to_string(),
+            minecraft_version: instance.mc_ver.clone(),
+            loader_version: instance.loader_ver.clone(),
+            jvm: options.jvm.clone(),
+            memory_mb: options.memory_mb,
+            memory: 0,
+            heap: None,
+            args: options.args.clone(),
+            seed: options.seed.clone(),
+            radius: options.radius,
+            cores: options.cores,
+            c2me_threads: options.c2me_threads,
+            average_cps,
+            samples,
+            mods,
+            system: SystemInfo::get().ok(),
+            gc: GcSummary::read(instance).ok(),
+            foreign: false,
+            preflight: Vec::new(),
+            profile: None,
+        }
+    }
+
+    /// Appends the run to the runs file
+    pub fn save(&self) -> io::Result<()> {
+        let mut runs = load_runs();
+        runs.push(self.clone());
+        let json = serde_json::to_string_pretty(&runs)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        fs::write(runs_file(), json)
+    }
+
+    /// Moves fields of runs stored by older versions to their current form
+    pub fn upgrade(mut self) -> Self {
+        if self.memory_mb == 0 {
+            self.memory_mb = self.memory * 1024;
+        }
+        self
+    }
+
+    /// Key shared by the iterations of one configuration within a batch: `batch` or `batch/label`
+    pub fn group_key(&self) -> String {
+        if self.label.is_empty() {
+            self.batch.clone()
+        } else {
+            format!("{}/{}", self.batch, self.label)
+        }
+    }
+}
+
+/// Stored runs grouped by `group_key`, oldest group first
+pub fn run_groups() -> Vec<(String, Vec<BenchmarkRun>)> {
+    group_runs(load_runs())
+}
+
+/// `runs` grouped by `group_key`, in order of their first run
+pub fn group_runs(runs: Vec<BenchmarkRun>) -> Vec<(String, Vec<BenchmarkRun>)> {
+    let mut groups: Vec<(String, Vec<BenchmarkRun>)> = Vec::new();
+    for run in runs {
+        let key = run.group_key();
+        match groups.iter_mut().find(|(k, _)| *k == key) {
+            Some((_, runs)) => runs.push(run),
+            None => groups.push((key, vec![run])),
+        }
+    }
+    groups
+}
+
+/// The runs of a group key, or the single run with that ID
+pub fn find_runs(key: &str) -> Vec<BenchmarkRun> {
+    if let Some((_, runs)) = run_groups().into_iter().find(|(k, _)| k == key) {
+        return runs;
+    }
+    load_runs().into_iter().filter(|run| run.id == key).collect()
+}
+
+/// Every stored run, oldest first
+pub fn load_runs() -> Vec<BenchmarkRun> {
+    fs::read_to_string(runs_file())
+        .ok()
+        .and_then(|json| serde_json::from_str::<Vec<BenchmarkRun>>(&json).ok())
+        .unwrap_or_default()
+        .into_iter()
+        .map(BenchmarkRun::upgrade)
+        .collect()
+}
+
+/// Mean ± standard deviation of every step's iterations, then each iteration with outliers flagged.
+/// A single run shows its CPS with its preflight warnings and flight recording summary instead.
+pub fn iteration_report(results: &[Vec<BenchmarkRun>]) -> Vec<String> {
+    if let [runs] = results
+        && let [run] = runs.as_slice()
+    {
+        let mut lines = vec![format!("{:.1} CPS", run.average_cps)];
+        lines.extend(run.preflight.iter().map(|warning| format!("Warning: {}", warning)));
+        lines.extend(run.profile.iter().flat_map(JfrSummary::lines));
+        return lines;
+    }
+
+    let mut lines = Vec::new();
+    for runs in results {
+        let Some(first) = runs.first() else {
+            continue;
+        };
+        let averages: Vec<f32> = runs.iter().map(|run| run.average_cps).collect();
+        let label = if first.label.is_empty() { "Result" } else { first.label.as_str() };
+        lines.push(format!(
+            "{}: {:.1} ± {:.1} CPS over {} iterations",
+            label,
+            mean(&averages),
+            stddev(&averages),
+            runs.len()
+        ));
+
+        let outliers = outliers(&averages);
+        for (i, run) in runs.iter().enumerate() {
+            let flag = if outliers.contains(&i) { " (outlier)" } else { "" };
+            lines.push(format!("Iteration {}: {:.1} CPS{}", run.iteration, run.average_cps, flag));
+        }
+    }
+    lines
+}
@@ -0,0 +1,59 @@
+use crate::benchmark::LaunchOptions;
+use crate::runs::BenchmarkRun;
+use crate::stats::mean;
+
+
+const CHART_WIDTH: usize = 30;
+
+/// 1, 2, 4, … cores, ending with `max_cores` itself
+pub fn core_counts(max_cores: u32) -> Vec<u32> {
+    let mut counts: Vec<u32> = std::iter::successors(Some(1u32), |n| n.checked_mul(2))
+        .take_while(|&n| n < max_cores)
+        .collect();
+    counts.push(max_cores.max(1));
+    counts
+}
+
+/// One step per core count, labelled `N cores`
+pub fn scaling_steps(options: &LaunchOptions, max_cores: u32) -> Vec<(String, LaunchOptions)> {
+    core_counts(max_cores)
+        .into_iter()
+        .map(|cores| {
+            let label = if cores == 1 { "1 core".to_string() } else { format!("{} cores", cores) };
+            (label, LaunchOptions { cores: Some(cores), ..options.clone() })
+        })
+        .collect()
+}
+
+/// CPS per core count with the speedup and parallel efficiency against the first step,
+/// followed by a bar chart of CPS against cores
+pub fn scaling_report(results: &[Vec<BenchmarkRun>]) -> Vec<String> {
+    let points: Vec<(&str, u32, f64)> = results
+        .iter()
+        .filter_map(|runs| {
+            let first = runs.first()?;
+            let averages: Vec<f32> = runs.iter().map(|run| run.average_cps).collect();
+            Some((first.label.as_str(), first.cores?, mean(&averages)))
+        })
+        .collect();
+    let Some(&(_, base_cores, base_cps)) = points.first() else {
+        return Vec::new();
+    };
+
+    let mut lines = Vec::new();
+    for &(label, cores, cps) in &points {
+        let speedup = if base_cps > 0.0 { cps / base_cps } else { 0.0 };
+        let efficiency = speedup * base_cores as f64 / cores as f64;
+        lines.push(format!(
+            "{}: {:.1} CPS, {:.2}x speedup, {:.0}% efficiency",
+            label, cps, speedup, efficiency * 100.0
+        ));
+    }
+
+    let max = points.iter().map(|&(_, _, cps)| cps).fold(0.0, f64::max);
+    for &(_, cores, cps) in &points {
+        let bar = if max > 0.0 { (cps / max * CHART_WIDTH as f64).round() as usize } else { 0 };
+        lines.push(format!("{:>3} │{} {:.0}", cores, "█".repeat(bar), cps));
+    }
+    lines
+}
@@ -0,0 +1,74 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::time::{SystemTime, UNIX_EPOCH};
+use slint::{FilterModel, Model, ModelRc, SharedString, VecModel};
+use crate::io::run_dir;
+use crate::{Info, LogLevel, LogLine};
+
+const SERVER_LOG: &str = "server.log";
+
+/// Level of a console line. Vanilla and loaders print `[12:00:00] [Server thread/WARN]: …`,
+/// Paper and its forks `[12:00:00 WARN]: …`. Untagged lines such as stack traces carry on
+/// the level of the line before them.
+pub fn log_level(line: &str, stderr: bool, previous: LogLevel) -> LogLevel {
+    let tagged = |level: &str| line.contains(&format!("/{}]", level)) || line.contains(&format!(" {}]", level));
+    if tagged("ERROR") || tagged("FATAL") {
+        LogLevel::Error
+    } else if tagged("WARN") {
+        LogLevel::Warn
+    } else if tagged("INFO") || tagged("DEBUG") {
+        LogLevel::Info
+    } else if line.starts_with(char::is_whitespace) || line.starts_with("Caused by") {
+        previous
+    } else if stderr {
+        // The JVM reports its own problems on stderr as `… warning: …`
+        if line.to_lowercase().contains("warning") { LogLevel::Warn } else { LogLevel::Error }
+    } else {
+        LogLevel::Info
+    }
+}
+
+/// Clears the log pane for a new run and returns the model its lines go into
+pub fn reset_log(info: &Info) -> Rc<VecModel<LogLine>> {
+    let log = Rc::new(VecModel::default());
+    info.set_server_log(ModelRc::from(log.clone()));
+    info.set_log_status(SharedString::new());
+    filter_log(info);
+    log
+}
+
+pub fn push_line(log: &VecModel<LogLine>, line: String, stderr: bool) {
+    let previous = log.row_data(log.row_count().wrapping_sub(1)).map_or(LogLevel::Info, |last| last.level);
+    let level = log_level(&line, stderr, previous);
+    log.push(LogLine { text: SharedString::from(line), level });
+}
+
+/// Shows the lines containing the search text, ignoring case
+pub fn filter_log(info: &Info) {
+    let search = info.get_log_filter().to_lowercase();
+    let visible = FilterModel::new(info.get_server_log(), move |line: &LogLine| line.text.to_lowercase().contains(&search));
+    info.set_visible_log(ModelRc::new(visible));
+}
+
+pub fn log_text(log: &impl Model<Data = LogLine>) -> String {
+    log.iter().map(|line| format!("{}\n", line.text)).collect()
+}
+
+/// Writes the whole log, not just the matching lines, to `subchunker-server-<timestamp>.log` in `dir`
+pub fn save_log(info: &Info, dir: &Path) -> io::Result<PathBuf> {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+    let path = dir.join(format!("subchunker-server-{}.log", now));
+    fs::write(&path, log_text(&info.get_server_log()))?;
+    Ok(path)
+}
+
+/// Keeps a run's console output in `data/runs/<id>/server.log`
+pub fn keep_log(run_id: &str, text: &str) -> io::Result<PathBuf> {
+    let dir = run_dir(run_id);
+    fs::create_dir_all(&dir)?;
+    let path = dir.join(SERVER_LOG);
+    fs::write(&path, text)?;
+    Ok(path)
+}
@@ -0,0 +1,139 @@
+use std::{fs, io};
+use serde::{Deserialize, Serialize};
+use log::error;
+use slint::{Model, SharedString};
+use crate::io::settings_file;
+use crate::memory::MIN_RAM_MB;
+use crate::slint_utils::{checked_names, toggled_names};
+use crate::Info;
+
+/// User choices persisted between launches
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct Settings {
+    /// Whether the user agreed to the Minecraft EULA; servers are never installed without it
+    pub eula_accepted: bool,
+    pub minecraft_version: Option<String>,
+    pub platform: Option<String>,
+    pub loader_version: Option<String>,
+    pub jvm: Option<String>,
+    /// Names of the checked entries; `None` keeps the defaults
+    pub gcs: Option<Vec<String>>,
+    pub args: Option<Vec<String>>,
+    pub mods: Option<Vec<String>>,
+    /// Heap size in MB
+    pub ram_alloc_mb: Option<i32>,
+    /// Heap size in GB, only read from settings saved before sizes were in MB
+    #[serde(skip_serializing)]
+    pub ram_alloc: Option<i32>,
+    pub seed: Option<String>,
+    pub radius: Option<i32>,
+    pub iterations: Option<i32>,
+    pub cooldown: Option<i32>,
+    pub cores: Option<i32>,
+    pub gc_threads: Option<i32>,
+    pub c2me_threads: Option<i32>,
+    /// Whether the user opted in to uploading runs to the online leaderboard
+    pub leaderboard_consent: bool,
+    /// Base URL of the leaderboard API, only set by editing the file.
+    /// `SUBCHUNKER_LEADERBOARD_URL` takes precedence.
+    pub leaderboard_url: Option<String>,
+}
+
+impl Settings {
+    pub fn load() -> Self {
+        fs::read_to_string(settings_file())
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        fs::write(settings_file(), json)
+    }
+
+    /// Saves the current Configure panel, keeping the other settings as they are on disk
+    pub fn store(info: &Info) {
+        let mut settings = Settings::load();
+        settings.update_from(info);
+        if let Err(e) = settings.save() {
+            error!("Failed to save settings: {}", e);
+        }
+    }
+
+    /// Restores the Configure panel. Values that are no longer offered are skipped.
+    /// The loader version is restored separately once the loader list has been fetched.
+    pub fn apply_to(&self, info: &Info) {
+        if let Some(mc_ver) = &self.minecraft_version
+            && info.get_stable_minecraft_versions().iter().any(|v| v == mc_ver.as_str())
+        {
+            info.set_selected_minecraft_version(SharedString::from(mc_ver));
+        }
+        if let Some(platform) = &self.platform
+            && info.get_platforms().iter().any(|p| p == platform.as_str())
+        {
+            info.set_selected_platform(SharedString::from(platform));
+        }
+        if let Some(jvm) = &self.jvm
+            && info.get_jvms().iter().any(|j| j == jvm.as_str())
+        {
+            info.set_selected_jvm(SharedString::from(jvm));
+        }
+        if let Some(gcs) = &self.gcs {
+            info.set_gcs_toggle(checked_names(&info.get_gcs(), gcs));
+        }
+        if let Some(args) = &self.args {
+            info.set_args_toggle(checked_names(&info.get_args(), args));
+        }
+        if let Some(mods) = &self.mods {
+            info.set_recommended_mods_toggle(checked_names(&info.get_recommended_mod_list(), mods));
+        }
+        if let Some(ram) = self.ram_alloc_mb.or(self.ram_alloc.map(|gb| gb * 1024)) {
+            info.set_ram_alloc(ram.clamp(MIN_RAM_MB as i32, (info.get_memory_capacity_gb() * 1024).max(MIN_RAM_MB as i32)));
+        }
+        if let Some(seed) = &self.seed {
+            info.set_seed(SharedString::from(seed));
+        }
+        if let Some(radius) = self.radius {
+            info.set_radius(radius);
+        }
+        if let Some(iterations) = self.iterations {
+            info.set_iterations(iterations.clamp(1, 10));
+        }
+        if let Some(cooldown) = self.cooldown {
+            info.set_cooldown(cooldown.max(0));
+        }
+        if let Some(cores) = self.cores {
+            info.set_cores(cores.clamp(0, info.get_logical_cpu_processors()));
+        }
+        if let Some(threads) = self.gc_threads {
+            info.set_gc_threads(threads.max(0));
+        }
+        if let Some(threads) = self.c2me_threads {
+            info.set_c2me_threads(threads.max(0));
+        }
+        info.set_leaderboard_consent(self.leaderboard_consent);
+    }
+
+    /// Picks up the current state of the Configure panel
+    pub fn update_from(&mut self, info: &Info) {
+        self.minecraft_version = Some(info.get_selected_minecraft_version().to_string());
+        self.platform = Some(info.get_selected_platform().to_string());
+        self.loader_version = Some(info.get_selected_loader_version().to_string());
+        self.jvm = Some(info.get_selected_jvm().to_string());
+        self.gcs = Some(toggled_names(&info.get_gcs(), &info.get_gcs_toggle()));
+        self.args = Some(toggled_names(&info.get_args(), &info.get_args_toggle()));
+        self.mods = Some(toggled_names(&info.get_recommended_mod_list(), &info.get_recommended_mods_toggle()));
+        self.ram_alloc_mb = Some(info.get_ram_alloc());
+        self.seed = Some(info.get_seed().to_string());
+        self.radius = Some(info.get_radius());
+        self.iterations = Some(info.get_iterations());
+        self.cooldown = Some(info.get_cooldown());
+        self.cores = Some(info.get_cores());
+        self.gc_threads = Some(info.get_gc_threads());
+        self.c2me_threads = Some(info.get_c2me_threads());
+        self.leaderboard_consent = info.get_leaderboard_consent();
+    }
+}
@@ -1,23 +1,34 @@
-use slint::{ModelRc, SharedString, VecModel};
+use slint::{Model, ModelRc, SharedString, VecModel};
 
-pub fn string_vec_to_rc(vec: &Vec<String>) -> ModelRc<SharedString> {
-    let shared_voices: Vec<SharedString> = vec.into_iter().map(SharedString::from).collect();
+pub fn string_vec_to_rc(vec: &[String]) -> ModelRc<SharedString> {
+    let shared_voices: Vec<SharedString> = vec.iter().map(SharedString::from).collect();
     let vec_model = VecModel::from(shared_voices);
     ModelRc::new(vec_model)
 }
 
-pub fn string_arr_to_rc(arr: &[&str]) -> ModelRc<SharedString> {
-    let shared_voices: Vec<SharedString> = arr.into_iter().map(|x| {
-        SharedString::from(*x)
-    }).collect();
+pub fn bool_arr_to_rc(arr: &[bool]) -> ModelRc<bool> {
+    let shared_voices: Vec<bool> = arr.to_vec();
     let vec_model = VecModel::from(shared_voices);
     ModelRc::new(vec_model)
 }
 
-pub fn bool_arr_to_rc(arr: &[bool]) -> ModelRc<bool> {
-    let shared_voices: Vec<bool> = arr.into_iter().map(|x| {
-        *x
-    }).collect();
-    let vec_model = VecModel::from(shared_voices);
+pub fn bool_vec_to_rc(vec: Vec<bool>) -> ModelRc<bool> {
+    let vec_model = VecModel::from(vec);
     ModelRc::new(vec_model)
 }
+
+/// Toggles for `names`, set where the name is in `checked`
+pub fn checked_names(names: &ModelRc<SharedString>, checked: &[String]) -> ModelRc<bool> {
+    let toggles: Vec<bool> = names.iter().map(|name| checked.iter().any(|c| c == name.as_str())).collect();
+    bool_vec_to_rc(toggles)
+}
+
+/// The names whose toggle is set
+pub fn toggled_names(names: &ModelRc<SharedString>, toggles: &ModelRc<bool>) -> Vec<String> {
+    names
+        .iter()
+        .zip(toggles.iter())
+        .filter(|(_, toggled)| *toggled)
+        .map(|(name, _)| name.to_string())
+        .collect()
+}
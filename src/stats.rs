@@ -0,0 +1,315 @@
+// Two-sided 95% quantile of the normal distribution
+pub const Z_95: f64 = 1.96;
+
+pub fn mean(samples: &[f32]) -> f64 {
+    if samples.is_empty() {
+        return 0.0;
+    }
+    samples.iter().map(|&s| s as f64).sum::<f64>() / samples.len() as f64
+}
+
+/// Sample variance, zero for fewer than two samples
+pub fn variance(samples: &[f32]) -> f64 {
+    if samples.len() < 2 {
+        return 0.0;
+    }
+    let mean = mean(samples);
+    samples.iter().map(|&s| (s as f64 - mean).powi(2)).sum::<f64>() / (samples.len() - 1) as f64
+}
+
+pub fn stddev(samples: &[f32]) -> f64 {
+    variance(samples).sqrt()
+}
+
+pub fn median(samples: &[f32]) -> f64 {
+    if samples.is_empty() {
+        return 0.0;
+    }
+    let mut sorted: Vec<f64> = samples.iter().map(|&s| s as f64).collect();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    let middle = sorted.len() / 2;
+    if sorted.len().is_multiple_of(2) {
+        (sorted[middle - 1] + sorted[middle]) / 2.0
+    } else {
+        sorted[middle]
+    }
+}
+
+/// Indices of values whose modified z-score, based on the median absolute deviation, exceeds 3.5
+pub fn outliers(values: &[f32]) -> Vec<usize> {
+    let center = median(values);
+    let deviations: Vec<f32> = values.iter().map(|&v| (v as f64 - center).abs() as f32).collect();
+    let mad = median(&deviations);
+    if mad == 0.0 {
+        return Vec::new();
+    }
+    values
+        .iter()
+        .enumerate()
+        .filter(|(_, v)| 0.6745 * (**v as f64 - center).abs() / mad > 3.5)
+        .map(|(i, _)| i)
+        .collect()
+}
+
+/// `mean(b) - mean(a)` and the half width of its 95% confidence interval
+pub fn mean_difference(a: &[f32], b: &[f32]) -> (f64, f64) {
+    let difference = mean(b) - mean(a);
+    let standard_error = (variance(a) / a.len().max(1) as f64 + variance(b) / b.len().max(1) as f64).sqrt();
+    (difference, t_95(a, b) * standard_error)
+}
+
+/// `mean(b) / mean(a)` and the half width of its 95% confidence interval, by the delta method
+pub fn speedup(a: &[f32], b: &[f32]) -> (f64, f64) {
+    let (mean_a, mean_b) = (mean(a), mean(b));
+    if mean_a == 0.0 || mean_b == 0.0 {
+        return (0.0, 0.0);
+    }
+    let ratio = mean_b / mean_a;
+    let relative_error = (variance(a) / (a.len().max(1) as f64 * mean_a.powi(2))
+        + variance(b) / (b.len().max(1) as f64 * mean_b.powi(2)))
+    .sqrt();
+    (ratio, t_95(a, b) * ratio * relative_error)
+}
+
+/// Two-sided p-value of Welch's t-test for a difference in means. `None` with fewer than two samples on a side.
+pub fn welch_t_test(a: &[f32], b: &[f32]) -> Option<f64> {
+    if a.len() < 2 || b.len() < 2 {
+        return None;
+    }
+    let error = variance(a) / a.len() as f64 + variance(b) / b.len() as f64;
+    if error == 0.0 {
+        return Some(if mean(a) == mean(b) { 1.0 } else { 0.0 });
+    }
+    let t = (mean(b) - mean(a)) / error.sqrt();
+    Some(t_p_value(t, welch_df(a, b)?))
+}
+
+/// Welch–Satterthwaite degrees of freedom, `None` with fewer than two samples on a side or no variance
+fn welch_df(a: &[f32], b: &[f32]) -> Option<f64> {
+    if a.len() < 2 || b.len() < 2 {
+        return None;
+    }
+    let error_a = variance(a) / a.len() as f64;
+    let error_b = variance(b) / b.len() as f64;
+    let df = (error_a + error_b).powi(2)
+        / (error_a.powi(2) / (a.len() - 1) as f64 + error_b.powi(2) / (b.len() - 1) as f64);
+    df.is_finite().then_some(df)
+}
+
+/// Two-sided p-value of `t` under Student's t distribution with `df` degrees of freedom
+fn t_p_value(t: f64, df: f64) -> f64 {
+    incomplete_beta(df / 2.0, 0.5, df / (df + t * t))
+}
+
+/// Two-sided 95% quantile of the t distribution for comparing `a` and `b`. A handful of runs
+/// per side needs the wider t interval, falls back to the normal quantile without variance.
+fn t_95(a: &[f32], b: &[f32]) -> f64 {
+    let Some(df) = welch_df(a, b) else {
+        return Z_95;
+    };
+    // The p-value falls as t grows, bisect for the t where it crosses 5%
+    let (mut low, mut high) = (0.0, 1000.0);
+    for _ in 0..100 {
+        let t = (low + high) / 2.0;
+        if t_p_value(t, df) > 0.05 {
+            low = t;
+        } else {
+            high = t;
+        }
+    }
+    (low + high) / 2.0
+}
+
+/// Two-sided p-value of the Mann-Whitney U test, using the normal approximation with tie correction.
+/// `None` with fewer than two samples on a side.
+pub fn mann_whitney_u(a: &[f32], b: &[f32]) -> Option<f64> {
+    if a.len() < 2 || b.len() < 2 {
+        return None;
+    }
+    let (n_a, n_b) = (a.len() as f64, b.len() as f64);
+    let n = n_a + n_b;
+
+    // Rank both samples together, ties share their average rank
+    let mut values: Vec<(f32, bool)> = a.iter().map(|&v| (v, true)).chain(b.iter().map(|&v| (v, false))).collect();
+    values.sort_by(|x, y| x.0.total_cmp(&y.0));
+    let mut rank_sum_a = 0.0;
+    let mut tie_term = 0.0;
+    let mut i = 0;
+    while i < values.len() {
+        let mut j = i;
+        while j + 1 < values.len() && values[j + 1].0 == values[i].0 {
+            j += 1;
+        }
+        let rank = (i + j) as f64 / 2.0 + 1.0;
+        let ties = (j - i + 1) as f64;
+        tie_term += ties.powi(3) - ties;
+        rank_sum_a += rank * values[i..=j].iter().filter(|(_, in_a)| *in_a).count() as f64;
+        i = j + 1;
+    }
+
+    let u = rank_sum_a - n_a * (n_a + 1.0) / 2.0;
+    let mean_u = n_a * n_b / 2.0;
+    let sigma = (n_a * n_b / 12.0 * ((n + 1.0) - tie_term / (n * (n - 1.0)))).sqrt();
+    if sigma == 0.0 {
+        return Some(1.0);
+    }
+    // Continuity correction
+    let z = ((u - mean_u).abs() - 0.5).max(0.0) / sigma;
+    Some(erfc(z / std::f64::consts::SQRT_2))
+}
+
+/// Complementary error function, accurate to about 1e-7
+fn erfc(x: f64) -> f64 {
+    let z = x.abs();
+    let t = 1.0 / (1.0 + 0.5 * z);
+    let r = t * (-z * z - 1.26551223
+        + t * (1.00002368
+            + t * (0.37409196
+                + t * (0.09678418
+                    + t * (-0.18628806
+                        + t * (0.27886807
+                            + t * (-1.13520398 + t * (1.48851587 + t * (-0.82215223 + t * 0.17087277)))))))))
+        .exp();
+    if x >= 0.0 { r } else { 2.0 - r }
+}
+
+fn ln_gamma(x: f64) -> f64 {
+    // Lanczos approximation
+    const COEFFICIENTS: [f64; 6] = [
+        76.18009172947146,
+        -86.50532032941677,
+        24.01409824083091,
+        -1.231739572450155,
+        0.1208650973866179e-2,
+        -0.5395239384953e-5,
+    ];
+    let tmp = x + 5.5;
+    let tmp = tmp - (x + 0.5) * tmp.ln();
+    let mut series = 1.000000000190015;
+    for (i, c) in COEFFICIENTS.iter().enumerate() {
+        series += c / (x + 1.0 + i as f64);
+    }
+    -tmp + (2.5066282746310005 * series / x).ln()
+}
+
+/// Regularized incomplete beta function I_x(a, b)
+fn incomplete_beta(a: f64, b: f64, x: f64) -> f64 {
+    if x <= 0.0 {
+        return 0.0;
+    }
+    if x >= 1.0 {
+        return 1.0;
+    }
+    let front = (ln_gamma(a + b) - ln_gamma(a) - ln_gamma(b) + a * x.ln() + b * (1.0 - x).ln()).exp();
+    // The continued fraction converges quickly on this side of the mean
+    if x < (a + 1.0) / (a + b + 2.0) {
+        front * beta_continued_fraction(a, b, x) / a
+    } else {
+        1.0 - front * beta_continued_fraction(b, a, 1.0 - x) / b
+    }
+}
+
+fn beta_continued_fraction(a: f64, b: f64, x: f64) -> f64 {
+    const TINY: f64 = 1e-300;
+    let mut c = 1.0;
+    let mut d = 1.0 - (a + b) * x / (a + 1.0);
+    if d.abs() < TINY {
+        d = TINY;
+    }
+    d = 1.0 / d;
+    let mut result = d;
+    for m in 1..200 {
+        let m = m as f64;
+        // Even step
+        let numerator = m * (b - m) * x / ((a + 2.0 * m - 1.0) * (a + 2.0 * m));
+        d = 1.0 + numerator * d;
+        d = if d.abs() < TINY { 1.0 / TINY } else { 1.0 / d };
+        c = 1.0 + numerator / c;
+        if c.abs() < TINY {
+            c = TINY;
+        }
+        result *= d * c;
+        // Odd step
+        let numerator = -(a + m) * (a + b + m) * x / ((a + 2.0 * m) * (a + 2.0 * m + 1.0));
+        d = 1.0 + numerator * d;
+        d = if d.abs() < TINY { 1.0 / TINY } else { 1.0 / d };
+        c = 1.0 + numerator / c;
+        if c.abs() < TINY {
+            c = TINY;
+        }
+        let step = d * c;
+        result *= step;
+        if (step - 1.0).abs() < 1e-12 {
+            break;
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: f64, expected: f64, tolerance: f64) {
+        assert!((actual - expected).abs() < tolerance, "{} is not {}", actual, expected);
+    }
+
+    // Reference values computed with mpmath at 30 digits
+
+    #[test]
+    fn erfc_values() {
+        assert_close(erfc(0.0), 1.0, 1e-7);
+        assert_close(erfc(0.5), 0.4795001221869535, 1e-7);
+        assert_close(erfc(1.0), 0.1572992070502851, 1e-7);
+        assert_close(erfc(-1.0), 1.842700792949715, 1e-7);
+        assert_close(erfc(2.0), 0.004677734981047266, 1e-7);
+    }
+
+    #[test]
+    fn incomplete_beta_values() {
+        // I_x(1, 1) = x
+        assert_close(incomplete_beta(1.0, 1.0, 0.3), 0.3, 1e-9);
+        // Binomial tail: P(X >= 2) for X ~ Bin(4, 0.4)
+        assert_close(incomplete_beta(2.0, 3.0, 0.4), 0.5248, 1e-9);
+        assert_close(incomplete_beta(3.0, 3.0, 0.5), 0.5, 1e-9);
+        assert_eq!(incomplete_beta(2.0, 3.0, 0.0), 0.0);
+        assert_eq!(incomplete_beta(2.0, 3.0, 1.0), 1.0);
+    }
+
+    #[test]
+    fn welch() {
+        // t = 2.449, df = 4
+        assert_close(welch_t_test(&[100.0, 110.0, 120.0], &[120.0, 130.0, 140.0]).unwrap(), 0.07048399691021995, 1e-6);
+        // Unequal variances, df = 6.972
+        assert_close(
+            welch_t_test(&[1.0, 2.0, 3.0, 4.0, 5.0], &[2.0, 4.0, 6.0, 8.0, 10.0, 12.0]).unwrap(),
+            0.04928433820673052,
+            1e-6,
+        );
+        assert_eq!(welch_t_test(&[5.0, 5.0], &[5.0, 5.0]), Some(1.0));
+        assert_eq!(welch_t_test(&[1.0], &[2.0, 3.0]), None);
+    }
+
+    #[test]
+    fn mann_whitney() {
+        // U = 0, the same as R's wilcox.test(exact = FALSE)
+        assert_close(mann_whitney_u(&[1.0, 2.0, 3.0], &[4.0, 5.0, 6.0]).unwrap(), 0.0808555983700523, 1e-6);
+        // U = 2.5 with tied ranks
+        assert_close(mann_whitney_u(&[1.0, 2.0, 2.0, 3.0], &[2.0, 3.0, 4.0, 4.0]).unwrap(), 0.13416918012812583, 1e-6);
+        assert_eq!(mann_whitney_u(&[7.0, 7.0], &[7.0, 7.0]), Some(1.0));
+        assert_eq!(mann_whitney_u(&[1.0, 2.0], &[3.0]), None);
+    }
+
+    #[test]
+    fn confidence_intervals() {
+        let (a, b) = ([100.0, 110.0, 120.0], [120.0, 130.0, 140.0]);
+        // t quantile for df = 4 is 2.776
+        let (difference, margin) = mean_difference(&a, &b);
+        assert_close(difference, 20.0, 1e-9);
+        assert_close(margin, 22.669579355275197, 1e-6);
+        let (ratio, margin) = speedup(&a, &b);
+        assert_close(ratio, 130.0 / 110.0, 1e-9);
+        assert_close(margin, 0.2256015569156889, 1e-6);
+        assert_eq!(speedup(&[0.0, 0.0], &b), (0.0, 0.0));
+    }
+}
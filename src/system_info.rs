@@ -1,11 +1,35 @@
+use std::fs;
+use std::path::Path;
 use hwinfo_rs::hwinfo;
+use serde::{Deserialize, Serialize};
+use crate::io::data_dir;
 
+/// The machine a run happened on. Fields added after the first release default to zero or
+/// `None` when reading older runs.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Default)]
+#[serde(default)]
 pub struct SystemInfo {
     pub os: String,
     pub cpu: String,
+    /// Logical cores
     pub cores: i32,
     pub gpus: String,
     pub memory: f64,
+    pub physical_cores: i32,
+    pub base_clock_mhz: i64,
+    pub boost_clock_mhz: i64,
+    pub l2_cache_bytes: i64,
+    pub l3_cache_bytes: i64,
+    pub avx2: bool,
+    pub avx512: bool,
+    pub numa_nodes: Option<u32>,
+    /// Fastest RAM module, usually only readable with elevated rights
+    pub memory_speed_mhz: Option<i64>,
+    /// `NVMe`, `SSD` or `HDD` holding the data directory
+    pub storage: Option<String>,
+    pub kernel: String,
+    /// cpufreq scaling governor of the first core, e.g. `performance` or `powersave`
+    pub governor: Option<String>,
 }
 
 impl SystemInfo {
@@ -19,16 +43,18 @@ impl SystemInfo {
             cpus_vec[0].model_name.clone()
         };
         let cores: i32 = cpus_vec.iter().map(|cpu| cpu.num_logical_cores).sum();
+        let physical_cores: i32 = cpus_vec.iter().map(|cpu| cpu.num_physical_cores).sum();
+        let has_flag = |prefix: &str| cpus_vec[0].flags.iter().any(|flag| flag.to_lowercase().starts_with(prefix));
         let gpus_vec = hwinfo::gpus()?;
         let mut gpus: String = String::new();
         for gpu in gpus_vec {
             if !gpus.is_empty() {
-                gpus.push_str("\n");
+                gpus.push('\n');
             }
             if gpu.num_cores != 0 {
-                gpus.push_str(&*(gpu.name + " (" + gpu.num_cores.to_string().as_str() + ")"));
+                gpus.push_str(&(gpu.name + " (" + gpu.num_cores.to_string().as_str() + ")"));
             } else {
-                gpus.push_str(&*(gpu.name));
+                gpus.push_str(&gpu.name);
             }
         }
 
@@ -37,11 +63,120 @@ impl SystemInfo {
             cpu,
             cores,
             gpus,
-            memory: bytes_to_gb(mem.total_bytes)
+            memory: bytes_to_gb(mem.total_bytes),
+            physical_cores,
+            base_clock_mhz: cpus_vec[0].regular_clock_speed_mhz,
+            boost_clock_mhz: cpus_vec[0].max_clock_speed_mhz,
+            l2_cache_bytes: cpus_vec[0].l2_cache_size_bytes,
+            l3_cache_bytes: cpus_vec[0].l3_cache_size_bytes,
+            avx2: has_flag("avx2"),
+            avx512: has_flag("avx512"),
+            numa_nodes: numa_nodes(),
+            memory_speed_mhz: mem.modules.iter().map(|module| module.frequency_hz / 1_000_000).max().filter(|&mhz| mhz > 0),
+            storage: storage_type(&data_dir()),
+            kernel: os.kernel,
+            governor: fs::read_to_string("/sys/devices/system/cpu/cpu0/cpufreq/scaling_governor")
+                .ok()
+                .map(|governor| governor.trim().to_string()),
         })
     }
+
+    /// Clocks, caches, vector extensions, governor and storage on one line
+    pub fn details(&self) -> String {
+        let mut details = vec![format!(
+            "{:.1}/{:.1} GHz, L2 {} KB, L3 {} MB",
+            self.base_clock_mhz as f64 / 1000.0,
+            self.boost_clock_mhz as f64 / 1000.0,
+            self.l2_cache_bytes / 1024,
+            self.l3_cache_bytes / 1_048_576
+        )];
+        if self.avx512 {
+            details.push("AVX-512".to_string());
+        } else if self.avx2 {
+            details.push("AVX2".to_string());
+        }
+        if let Some(nodes) = self.numa_nodes.filter(|&nodes| nodes > 1) {
+            details.push(format!("{} NUMA nodes", nodes));
+        }
+        if let Some(speed) = self.memory_speed_mhz {
+            details.push(format!("RAM {} MHz", speed));
+        }
+        if let Some(governor) = &self.governor {
+            details.push(governor.clone());
+        }
+        if let Some(storage) = &self.storage {
+            details.push(storage.clone());
+        }
+        details.join(", ")
+    }
 }
 
 fn bytes_to_gb(bytes: i64) -> f64 {
     bytes as f64 / 1_073_741_824.0
+}
+
+/// Names of the fields that differ, ignoring ones missing from either side
+pub fn differences(a: &SystemInfo, b: &SystemInfo) -> Vec<&'static str> {
+    let mut differences = Vec::new();
+    let mut check = |name, differs: bool| {
+        if differs {
+            differences.push(name);
+        }
+    };
+    check("CPU", a.cpu != b.cpu || a.cores != b.cores);
+    check("memory", (a.memory - b.memory).abs() > 0.5);
+    check("OS", a.os != b.os);
+    check("clocks", a.boost_clock_mhz != 0 && b.boost_clock_mhz != 0 && a.boost_clock_mhz != b.boost_clock_mhz);
+    check("memory speed", a.memory_speed_mhz.zip(b.memory_speed_mhz).is_some_and(|(a, b)| a != b));
+    check("storage", a.storage.as_ref().zip(b.storage.as_ref()).is_some_and(|(a, b)| a != b));
+    check("kernel", !a.kernel.is_empty() && !b.kernel.is_empty() && a.kernel != b.kernel);
+    check("governor", a.governor.as_ref().zip(b.governor.as_ref()).is_some_and(|(a, b)| a != b));
+    differences
+}
+
+fn numa_nodes() -> Option<u32> {
+    let entries = fs::read_dir("/sys/devices/system/node").ok()?;
+    let count = entries
+        .flatten()
+        .filter(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            name.strip_prefix("node").is_some_and(|id| id.parse::<u32>().is_ok())
+        })
+        .count();
+    // Without node directories sysfs doesn't expose NUMA, so the count is unknown
+    (count > 0).then_some(count as u32)
+}
+
+/// Looks up the block device holding `path` in sysfs, following device mapper and RAID
+/// volumes down to the first disk underneath
+#[cfg(target_os = "linux")]
+fn storage_type(path: &Path) -> Option<String> {
+    use std::os::unix::fs::MetadataExt;
+
+    let dev = fs::metadata(path).ok()?.dev();
+    let major = ((dev >> 8) & 0xfff) | ((dev >> 32) & !0xfff);
+    let minor = (dev & 0xff) | ((dev >> 12) & !0xff);
+    let mut device = fs::canonicalize(format!("/sys/dev/block/{}:{}", major, minor)).ok()?;
+    for _ in 0..4 {
+        // Partitions sit inside the folder of their disk
+        if device.join("partition").exists() {
+            device.pop();
+        }
+        let slave = fs::read_dir(device.join("slaves")).ok().and_then(|mut slaves| slaves.next()?.ok());
+        match slave {
+            Some(slave) => device = fs::canonicalize(slave.path()).ok()?,
+            None => break,
+        }
+    }
+
+    if device.file_name()?.to_string_lossy().starts_with("nvme") {
+        return Some("NVMe".to_string());
+    }
+    let rotational = fs::read_to_string(device.join("queue/rotational")).ok()?;
+    Some(if rotational.trim() == "1" { "HDD" } else { "SSD" }.to_string())
+}
+
+#[cfg(not(target_os = "linux"))]
+fn storage_type(_path: &Path) -> Option<String> {
+    None
 }
\ No newline at end of file
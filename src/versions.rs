@@ -0,0 +1,202 @@
+use std::cmp::Ordering;
+
+/// A Fabric-style semantic version such as `1.21.4`, `0.16.10+build.1` or `1.21-rc.1`.
+/// `x`, `X` and `*` components act as wildcards when the version is used in a range.
+struct Version {
+    parts: Vec<Option<u64>>,
+    /// Dot separated pre-release identifiers. `1.21-` has an empty list, which sorts before every other pre-release.
+    pre: Option<Vec<String>>,
+}
+
+impl Version {
+    fn parse(version: &str) -> Option<Self> {
+        // Build metadata never affects precedence
+        let version = version.split('+').next()?;
+        let (core, pre) = match version.split_once('-') {
+            Some((core, "")) => (core, Some(Vec::new())),
+            Some((core, pre)) => (core, Some(pre.split('.').map(str::to_string).collect())),
+            None => (version, None),
+        };
+        if core.is_empty() {
+            return None;
+        }
+        let parts = core
+            .split('.')
+            .map(|part| match part {
+                "x" | "X" | "*" => Some(None),
+                _ => part.parse::<u64>().ok().map(Some),
+            })
+            .collect::<Option<Vec<_>>>()?;
+        Some(Version { parts, pre })
+    }
+
+    fn has_wildcard(&self) -> bool {
+        self.parts.iter().any(Option::is_none)
+    }
+
+    fn part(&self, index: usize) -> u64 {
+        self.parts.get(index).copied().flatten().unwrap_or(0)
+    }
+
+    fn cmp(&self, other: &Version) -> Ordering {
+        let len = self.parts.len().max(other.parts.len());
+        for i in 0..len {
+            match self.part(i).cmp(&other.part(i)) {
+                Ordering::Equal => continue,
+                ordering => return ordering,
+            }
+        }
+        match (&self.pre, &other.pre) {
+            (None, None) => Ordering::Equal,
+            (None, Some(_)) => Ordering::Greater,
+            (Some(_), None) => Ordering::Less,
+            (Some(a), Some(b)) => compare_pre(a, b),
+        }
+    }
+
+    /// Whether the components before the first wildcard equal `other`'s
+    fn matches_wildcard(&self, other: &Version) -> bool {
+        self.parts
+            .iter()
+            .enumerate()
+            .take_while(|(_, part)| part.is_some())
+            .all(|(i, part)| *part == Some(other.part(i)))
+    }
+}
+
+/// Numeric identifiers sort before alphanumeric ones, and a shorter list before a longer one it prefixes
+fn compare_pre(a: &[String], b: &[String]) -> Ordering {
+    for (x, y) in a.iter().zip(b) {
+        let ordering = match (x.parse::<u64>(), y.parse::<u64>()) {
+            (Ok(x), Ok(y)) => x.cmp(&y),
+            (Ok(_), Err(_)) => Ordering::Less,
+            (Err(_), Ok(_)) => Ordering::Greater,
+            (Err(_), Err(_)) => x.cmp(y),
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+    a.len().cmp(&b.len())
+}
+
+/// Whether `version` satisfies any of the Fabric version predicates in `ranges`, e.g. `>=1.21 <1.22`,
+/// `~1.21.4`, `^0.16`, `1.21.x` or `1.20.1 || >=1.21`. `None` when the version or a predicate can't be parsed.
+pub fn satisfies_any(version: &str, ranges: &[String]) -> Option<bool> {
+    let version = Version::parse(version)?;
+    let mut matched = false;
+    for range in ranges.iter().flat_map(|range| range.split("||")) {
+        matched |= satisfies(&version, range)?;
+    }
+    Some(matched)
+}
+
+/// Every space separated term of the predicate has to hold
+fn satisfies(version: &Version, range: &str) -> Option<bool> {
+    let mut matched = true;
+    for term in range.split_whitespace() {
+        if term == "*" {
+            continue;
+        }
+        let split = term.find(|c: char| c.is_ascii_alphanumeric())?;
+        let (operator, wanted) = term.split_at(split);
+        let wanted = Version::parse(wanted)?;
+        let ordering = version.cmp(&wanted);
+        matched &= match operator {
+            "" | "=" if wanted.has_wildcard() => wanted.matches_wildcard(version),
+            "" | "=" => ordering == Ordering::Equal,
+            ">=" => ordering != Ordering::Less,
+            "<=" => ordering != Ordering::Greater,
+            ">" => ordering == Ordering::Greater,
+            "<" => ordering == Ordering::Less,
+            // Same major version
+            "^" => ordering != Ordering::Less && version.part(0) == wanted.part(0),
+            // Same major and minor version
+            "~" => ordering != Ordering::Less && version.part(0) == wanted.part(0) && version.part(1) == wanted.part(1),
+            _ => return None,
+        };
+    }
+    Some(matched)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(version: &str, range: &str) -> Option<bool> {
+        satisfies_any(version, &[range.to_string()])
+    }
+
+    #[test]
+    fn ranges() {
+        let cases = [
+            ("1.21.4", ">=1.21 <1.22", true),
+            ("1.21", ">=1.21 <1.22", true),
+            ("1.22", ">=1.21 <1.22", false),
+            ("1.20.6", ">=1.21 <1.22", false),
+            ("1.21.4", "~1.21.4", true),
+            ("1.21.9", "~1.21.4", true),
+            ("1.21.3", "~1.21.4", false),
+            ("1.22.0", "~1.21.4", false),
+            ("0.16.10", "^0.16", true),
+            ("0.15.11", "^0.16", false),
+            // Fabric keeps the major version for `^` even below 1.0
+            ("0.17.0", "^0.16", true),
+            ("1.0.0", "^0.16", false),
+            ("1.21", "1.21.x", true),
+            ("1.21.4", "1.21.x", true),
+            ("1.22", "1.21.x", false),
+            ("1.21.4", "1.21.4", true),
+            ("1.21.4", "=1.21.5", false),
+            ("1.21.4", ">1.21.3", true),
+            ("1.21.4", "<=1.21.3", false),
+            ("0.1", "*", true),
+            ("1.21.4", "*", true),
+        ];
+        for (version, range, expected) in cases {
+            assert_eq!(check(version, range), Some(expected), "{} against {}", version, range);
+        }
+    }
+
+    #[test]
+    fn alternatives() {
+        assert_eq!(check("1.20.1", "1.20.1 || >=1.21"), Some(true));
+        assert_eq!(check("1.20.4", "1.20.1 || >=1.21"), Some(false));
+        assert_eq!(check("1.21.2", "1.20.1 || >=1.21"), Some(true));
+        // Separate entries are alternatives as well
+        assert_eq!(satisfies_any("1.21", &["1.20".to_string(), "1.21".to_string()]), Some(true));
+        assert_eq!(satisfies_any("1.21", &[]), Some(false));
+    }
+
+    #[test]
+    fn pre_releases() {
+        assert_eq!(check("1.21-rc1", "<1.21"), Some(true));
+        assert_eq!(check("1.21-rc1", ">=1.21"), Some(false));
+        assert_eq!(check("1.21-pre.2", "<1.21-rc.1"), Some(true));
+        assert_eq!(check("1.21-rc.2", ">1.21-rc.1"), Some(true));
+        // Numeric identifiers sort before alphanumeric ones, a prefix before the longer list
+        assert_eq!(check("1.21-rc.1", "<1.21-rc.beta"), Some(true));
+        assert_eq!(check("1.21-rc", "<1.21-rc.1"), Some(true));
+        assert_eq!(check("1.21-", "<1.21-alpha"), Some(true));
+    }
+
+    #[test]
+    fn build_metadata() {
+        assert_eq!(check("0.16.10+build.1", "0.16.10"), Some(true));
+        assert_eq!(check("0.16.10+build.1", ">0.16.10"), Some(false));
+        assert_eq!(check("0.16.10", "0.16.10+build.7"), Some(true));
+        let a = Version::parse("1.0.0+a").unwrap();
+        let b = Version::parse("1.0.0+b").unwrap();
+        assert_eq!(a.cmp(&b), Ordering::Equal);
+    }
+
+    #[test]
+    fn unparseable() {
+        assert_eq!(check("abc", ">=1.21"), None);
+        assert_eq!(check("", ">=1.21"), None);
+        assert_eq!(check("1.21", ">=foo"), None);
+        assert_eq!(check("1.21", "?1.21"), None);
+        assert_eq!(check("1.21", "!=1.21"), None);
+        assert_eq!(check("1.21", ">="), None);
+    }
+}